@@ -26,6 +26,8 @@ fn main() {
         .whitelisted_type("SWS.*")
         .whitelisted_var("SWS.*")
         .whitelisted_function("sws.*")
+        .whitelisted_type("Swr.*")
+        .whitelisted_function("swr.*")
         .whitelisted_type("RUST_AV.*")
         .whitelisted_var("SEEK_.*")
         .whitelisted_type(".*_t")
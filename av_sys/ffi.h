@@ -1,14 +1,37 @@
 #include <libavutil/avutil.h>
 #include <libavutil/pixfmt.h>
+#include <libavutil/pixdesc.h>
+#include <libavutil/samplefmt.h>
 #include <libavutil/imgutils.h>
 #include <libavutil/timestamp.h>
+#include <libavutil/audio_fifo.h>
+#include <libavutil/display.h>
+#include <libavutil/stereo3d.h>
+#include <libavutil/mastering_display_metadata.h>
+#include <libavutil/replaygain.h>
+#include <libavutil/motion_vector.h>
 #include <libavformat/avformat.h>
 #include <libavcodec/avcodec.h>
 #include <libswscale/swscale.h>
 #include <libswresample/swresample.h>
+#include <libavfilter/avfilter.h>
+#include <libavfilter/buffersrc.h>
+#include <libavfilter/buffersink.h>
 
 enum RUST_AV_CONSTANTS {
     RUST__i64__NOPTS_VALUE = AV_NOPTS_VALUE,
     RUST_OS_RAW__c_int__AVERROR_EAGAIN = AVERROR(EAGAIN),
     RUST_OS_RAW__c_int__AVERROR_EOF = AVERROR_EOF,
+    RUST_OS_RAW__c_int__AVERROR_EINVAL = AVERROR(EINVAL),
+    RUST_OS_RAW__c_int__AVERROR_ENOMEM = AVERROR(ENOMEM),
+    RUST_OS_RAW__c_int__AVERROR_EIO = AVERROR(EIO),
+    RUST_OS_RAW__c_int__AVERROR_ENOSYS = AVERROR(ENOSYS),
+    RUST_OS_RAW__c_int__AVERROR_INVALIDDATA = AVERROR_INVALIDDATA,
+    RUST_OS_RAW__c_int__AVERROR_DECODER_NOT_FOUND = AVERROR_DECODER_NOT_FOUND,
+    RUST_OS_RAW__c_int__AVERROR_ENCODER_NOT_FOUND = AVERROR_ENCODER_NOT_FOUND,
+    RUST_OS_RAW__c_int__AVERROR_STREAM_NOT_FOUND = AVERROR_STREAM_NOT_FOUND,
 };
+
+// The signature of `av_log_set_callback` callbacks, whose `va_list`
+// parameter type differs between platforms.
+typedef void (*RUST_AV_LOG_CALLBACK)(void *avcl, int level, const char *fmt, va_list vl);
@@ -0,0 +1,37 @@
+extern crate av;
+
+use av::codec::{
+    Codec,
+    CAP_EXPERIMENTAL,
+    CAP_LOSSLESS,
+};
+
+fn main() {
+    av::LibAV::init();
+
+    for codec in Codec::all() {
+        let kind = match (codec.is_decoder(), codec.is_encoder()) {
+            (true, true) => "DE",
+            (true, false) => "D ",
+            (false, true) => " E",
+            (false, false) => "  ",
+        };
+
+        let capabilities = codec.capabilities();
+        let mut notes = Vec::new();
+        if capabilities.contains(CAP_EXPERIMENTAL) {
+            notes.push("experimental".to_string());
+        }
+        if capabilities.contains(CAP_LOSSLESS) {
+            notes.push("lossless".to_string());
+        }
+
+        println!("{} {:?} {:<20} {:?} {}",
+            kind,
+            codec.media_type(),
+            codec.name().unwrap_or_default().to_string_lossy(),
+            codec.long_name().unwrap_or_default(),
+            notes.join(", "),
+        );
+    }
+}
@@ -21,6 +21,21 @@ fn decoding() -> av::Result<()> {
     demuxer.dump_info();
     println!("{:?}", demuxer);
 
+    for stream in demuxer.streams() {
+        let params = stream.codec_parameters();
+        println!("Stream #{}: {:?} {}", stream.index(), params.media_type(), params.codec_id());
+        println!("    language: {:?}", stream.language());
+        println!("    frame rate: {:?}", stream.guess_frame_rate().map(|rate| rate.as_f64()));
+        println!("    size: {} x {}", params.width(), params.height());
+        println!("    pixel format: {:?}", params.pixel_format());
+        println!("    sample format: {:?}, rate: {}", params.sample_format(), params.sample_rate());
+        println!("    bit rate: {:?}", params.bit_rate());
+    }
+
+    for chapter in demuxer.chapters() {
+        println!("{:?}", chapter);
+    }
+
     let mut num_packets = 0;
 
     while demuxer.read_packet()?.is_some() {
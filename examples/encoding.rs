@@ -3,14 +3,11 @@ extern crate error_chain;
 extern crate av;
 
 use std::fs::File;
-use av::codec::Codec;
+use av::codec::{Codec, CodecId};
 use av::format::{
     Muxer,
     OutputFormat,
 };
-use av::ffi::AVPixelFormat::*;
-use av::ffi::AVSampleFormat::*;
-use av::ffi::AVCodecID::*;
 use av::ffi::AVRational;
 use av::ffi;
 use av::audio::constants::CHANNEL_LAYOUT_MONO;
@@ -51,7 +48,7 @@ pub fn demo() -> av::Result<()> {
         let height = 400;
         let framerate = 30;
         let align = 32;
-        let video_codec = Codec::find_encoder_by_id(AV_CODEC_ID_H264)?;
+        let video_codec = Codec::find_encoder_by_id(CodecId::H264)?;
         let video_encoder = video::Encoder::from_codec(video_codec)?
             .width(width)
             .height(height)
@@ -59,7 +56,7 @@ pub fn demo() -> av::Result<()> {
             .time_base(framerate)
             .open(output_format)?;
 
-        frames.push(video::Frame::new(width, height, AV_PIX_FMT_RGB24, align)?.into());
+        frames.push(video::Frame::new(width, height, video::PixelFormat::Rgb24, align)?.into());
         timestamps.push(Ts::new(0, video_encoder.time_base()));
         encoders.push(video_encoder.into());
     }
@@ -68,9 +65,9 @@ pub fn demo() -> av::Result<()> {
     // Create audio encoder
     {
         let sample_rate = 44100;
-        let sample_format = AV_SAMPLE_FMT_FLTP;
+        let sample_format = audio::SampleFormat::Fltp;
         let channel_layout = CHANNEL_LAYOUT_MONO;
-        let audio_codec = Codec::find_encoder_by_id(AV_CODEC_ID_AAC)?;
+        let audio_codec = Codec::find_encoder_by_id(CodecId::Aac)?;
         let audio_encoder = audio::Encoder::from_codec(audio_codec)?
             .sample_rate(sample_rate)
             .sample_format(sample_format)
@@ -122,7 +119,7 @@ pub fn demo() -> av::Result<()> {
 
                 // Render audio frame
                 frame.set_pts(ts.index());
-                render_audio(frame, &mut audio_data);
+                render_audio(frame, &mut audio_data)?;
                 *ts += frame.num_samples() as i64;
 
                 // Encode and mux audio frame
@@ -158,12 +155,15 @@ fn render_demo_bar(frame: &mut video::Frame, video_frame_buffer: &mut [u8], ts:
     frame.fill_channel(0, &video_frame_buffer)
 }
 
-fn render_audio(audio_frame: &mut audio::Frame, audio_data: &mut &[u8]) {
+fn render_audio(audio_frame: &mut audio::Frame, audio_data: &mut &[u8]) -> av::Result<()> {
     println!("### TODO: Do proper audio rendering");
-    println!("### frame_size: {}", audio_frame.data_mut()[0].len());
-    let buf_len = min(audio_data.len(), audio_frame.data_mut()[0].len());
+    let mut channels = audio_frame.data_mut()?;
+    println!("### frame_size: {}", channels[0].len());
+    let buf_len = min(audio_data.len(), channels[0].len());
 
-    audio_frame.data_mut()[0][..buf_len].copy_from_slice(&audio_data[..buf_len]);
+    channels[0][..buf_len].copy_from_slice(&audio_data[..buf_len]);
     *audio_data = &audio_data[buf_len..];
     println!("### Remaining audio bytes: {}", audio_data.len());
+
+    Ok(())
 }
@@ -1,30 +1,36 @@
 use std::ptr;
+use std::collections::VecDeque;
 use std::os::raw::c_int;
 use LibAV;
 use codec::{
     Codec,
     MediaType,
+    CAP_SMALL_LAST_FRAME,
+    CAP_VARIABLE_FRAME_SIZE,
 };
 use ffi::{
     self,
     AVCodecContext,
-    AVSampleFormat,
     AVRational,
     avcodec_alloc_context3,
     avcodec_free_context,
     av_get_channel_layout_nb_channels,
 };
-use ffi::AVSampleFormat::AV_SAMPLE_FMT_S16;
 use format::OutputFormat;
 use audio::ChannelLayout;
 use audio::constants::CHANNEL_LAYOUT_STEREO;
 use generic::RefMutFrame;
-use common::{self, Packet, Timebase};
+use common::{self, Packet, Timebase, Options};
 use errors::*;
 use util::OwnedOrRefMut;
+use super::{Frame, Resampler, AudioFifo, SampleFormat};
 
 pub struct Encoder {
     ptr: *mut AVCodecContext,
+    resampler: Resampler,
+    fifo: Option<AudioFifo>,
+    pending_packets: VecDeque<Packet<'static>>,
+    unused_options: Vec<String>,
 }
 unsafe impl Send for Encoder {}
 unsafe impl Sync for Encoder {}
@@ -34,8 +40,8 @@ impl Encoder {
         EncoderBuilder::from_codec(codec)
     }
 
-    pub fn sample_format(&self) -> AVSampleFormat {
-        self.as_ref().sample_fmt
+    pub fn sample_format(&self) -> SampleFormat {
+        SampleFormat::from_raw(self.as_ref().sample_fmt)
     }
 
     /// TODO: Check for underflow
@@ -43,6 +49,10 @@ impl Encoder {
         self.as_ref().sample_rate as u32
     }
 
+    pub fn channel_layout(&self) -> ChannelLayout {
+        ChannelLayout::from_bits_truncate(self.as_ref().channel_layout)
+    }
+
     pub fn time_base(&self) -> Timebase {
         self.as_ref().time_base.into()
     }
@@ -61,56 +71,142 @@ impl Encoder {
             Codec::from_ptr(self.as_ref().codec)
         }
     }
+
+    /// Codec options that were not consumed when opening the encoder.
+    pub fn unused_options(&self) -> &[String] {
+        &self.unused_options
+    }
 }
 
 impl Encoder {
+    /// Encode an audio frame of any length.
+    ///
+    /// Frames that don't match the encoder's sample rate, format or channel layout
+    /// are resampled. If the encoder requires a fixed frame size,
+    /// the samples are buffered and cut into frames of that size.
+    /// The pts of `frame` is expected in the encoder's time base (`1/sample_rate`).
     pub fn encode<'a, F>(&mut self, frame: F) -> Result<Packets> where
         F: Into<RefMutFrame<'a>>,
     {
-        unsafe {
-            let mut frame = frame.into().into_audio_frame()
-                .ok_or("Cannot encode non-audio frame as audio")?;
+        let frame = frame.into().into_audio_frame()
+            .ok_or("Cannot encode non-audio frame as audio")?;
+
+        // Do resampling if needed
+        if !frame.is_compatible_with_encoder(self) {
+            let sample_rate = self.sample_rate();
+            let sample_format = self.sample_format();
+            let channel_layout = self.channel_layout();
+
+            let mut tmp_frame = self.resampler.resample_frame(frame, sample_rate, sample_format, channel_layout)?;
+
+            // The resampler might still be buffering all of the samples
+            if tmp_frame.num_samples() > 0 {
+                self.queue_frame(&mut tmp_frame)?;
+            }
+        } else {
+            self.queue_frame(frame)?;
+        }
+
+        Ok(Packets::from_mut_encoder(self))
+    }
+
+    pub fn flush(mut self) -> Result<Packets<'static>> {
+        // Encode samples remaining in the resampler
+        if let Some(mut frame) = self.resampler.flush()? {
+            self.queue_frame(&mut frame)?;
+        }
 
-            // Do scaling if needed
-            // if !frame.is_compatible_with_encoder(self) {
-            //     self.update_scaler(frame)?;
-            //     self.init_tmp_frame()?;
+        // Encode the last partial frame
+        if let Some(mut frame) = self.take_last_frame()? {
+            self.send_frame(frame.as_mut_ptr())?;
+        }
+
+        // Flush encoder
+        self.send_frame(ptr::null_mut())?;
 
-            //     let tmp_frame = self.tmp_frame.as_mut().unwrap();
-            //     let scaler = self.scaler.as_mut().unwrap();
+        Ok(Packets::from_encoder(self))
+    }
 
-            //     scaler.scale_frame(&mut frame, tmp_frame);
+    /// Send `frame` to the encoder,
+    /// cutting it into frames of the required size first if necessary.
+    fn queue_frame(&mut self, frame: &mut Frame) -> Result<()> {
+        let frame_size = self.as_ref().frame_size as usize;
+
+        match self.fifo {
+            Some(ref mut fifo) => fifo.push(frame)?,
+            None => return self.send_frame(frame.as_mut_ptr()),
+        }
+
+        loop {
+            let mut frame = match self.fifo.as_mut().unwrap().pop(frame_size)? {
+                Some(frame) => frame,
+                None => return Ok(()),
+            };
+
+            self.send_frame(frame.as_mut_ptr())?;
+        }
+    }
 
-            //     // Copy frame data
-            //     tmp_frame.set_pts(frame.pts());
-            //     frame = tmp_frame;
-            // }        
+    /// The samples remaining in the fifo,
+    /// padded with silence if the encoder doesn't accept a smaller last frame.
+    fn take_last_frame(&mut self) -> Result<Option<Frame>> {
+        let frame_size = self.as_ref().frame_size as usize;
+        let small_last_frame = self.codec().capabilities().contains(CAP_SMALL_LAST_FRAME);
 
-            // Encode the frame
-            {
-                let mut packet = ::std::mem::zeroed();
-                ffi::av_init_packet(&mut packet);
+        match self.fifo {
+            Some(ref mut fifo) if small_last_frame => fifo.pop_remaining(),
+            Some(ref mut fifo) => fifo.pop_padded(frame_size),
+            None => Ok(None),
+        }
+    }
 
-                let res = ffi::avcodec_send_frame(self.ptr, frame.as_mut_ptr());
+    /// Send a frame (or `null` to flush) to the encoder.
+    /// If the encoder doesn't accept more input,
+    /// its packets are moved to `pending_packets` first.
+    fn send_frame(&mut self, frame: *mut ffi::AVFrame) -> Result<()> {
+        unsafe {
+            loop {
+                let res = ffi::avcodec_send_frame(self.ptr, frame);
+                if res == ffi::AVERROR_EAGAIN {
+                    let num_pending = self.pending_packets.len();
+                    while let Some(packet) = self.receive_packet()? {
+                        self.pending_packets.push_back(packet);
+                    }
+
+                    if num_pending != self.pending_packets.len() {
+                        continue;
+                    }
+                }
                 if res < 0 {
-                    bail!("Could not encode frame: 0x{:X}", res)
+                    if frame.is_null() {
+                        bail!(ErrorKind::av(res, "Could not flush encoder"))
+                    }
+                    bail!(ErrorKind::av(res, "Could not encode frame"))
                 }
 
+                return Ok(());
             }
-
-            Ok(Packets::from_mut_encoder(self))
         }
     }
 
-    pub fn flush(self) -> Result<Packets<'static>> {
+    fn receive_packet(&mut self) -> Result<Option<Packet<'static>>> {
         unsafe {
-            // Flush encoder
-            let res = ffi::avcodec_send_frame(self.ptr, ptr::null_mut());
+            let mut packet = ffi::av_packet_alloc();
+            if packet.is_null() {
+                bail!(ErrorKind::AllocFailed("packet"));
+            }
+
+            let res = ffi::avcodec_receive_packet(self.ptr, packet);
             if res < 0 {
-                bail!("Could not flush encoder: 0x{:X}", res)
+                ffi::av_packet_free(&mut packet);
+
+                match res {
+                    ffi::AVERROR_EAGAIN | ffi::AVERROR_EOF => return Ok(None),
+                    _ => bail!(ErrorKind::av(res, "Failed to receive packet")),
+                }
             }
 
-            Ok(Packets::from_encoder(self))
+            Ok(Some(Packet::from_ptr(packet, self.time_base())))
         }
     }
 }
@@ -139,9 +235,14 @@ impl Drop for Encoder {
 
 pub struct EncoderBuilder {
     codec: Codec,
-    sample_format: Option<AVSampleFormat>,
+    sample_format: Option<SampleFormat>,
     sample_rate: Option<u32>,
     channel_layout: Option<ChannelLayout>,
+    bitrate: Option<i64>,
+    compression_level: Option<u32>,
+    cutoff: Option<u32>,
+    profile: Option<c_int>,
+    options: Options,
 }
 
 impl EncoderBuilder {
@@ -154,10 +255,15 @@ impl EncoderBuilder {
             sample_format: None,
             sample_rate: None,
             channel_layout: None,
+            bitrate: None,
+            compression_level: None,
+            cutoff: None,
+            profile: None,
+            options: Options::new(),
         })
     }
 
-    pub fn sample_format(&mut self, sample_format: AVSampleFormat) -> &mut Self {
+    pub fn sample_format(&mut self, sample_format: SampleFormat) -> &mut Self {
         self.sample_format = Some(sample_format); self
     }
 
@@ -170,36 +276,188 @@ impl EncoderBuilder {
         self.channel_layout = Some(channel_layout); self
     }
 
+    /// Average bitrate in bits per second.
+    pub fn bitrate(&mut self, bitrate: i64) -> &mut Self {
+        self.bitrate = Some(bitrate); self
+    }
+
+    /// Codec specific compression level, e.g. `0`-`12` for FLAC.
+    pub fn compression_level(&mut self, compression_level: u32) -> &mut Self {
+        self.compression_level = Some(compression_level); self
+    }
+
+    /// Lowpass cutoff frequency in Hz.
+    pub fn cutoff(&mut self, cutoff: u32) -> &mut Self {
+        self.cutoff = Some(cutoff); self
+    }
+
+    /// Codec profile, e.g. `ffi::FF_PROFILE_AAC_HE`.
+    pub fn profile(&mut self, profile: c_int) -> &mut Self {
+        self.profile = Some(profile); self
+    }
+
+    /// Set a codec option, e.g. `option("aac_coder", "twoloop")`.
+    /// Fails if `key` or `value` contain a nul byte.
+    pub fn option(&mut self, key: &str, value: &str) -> Result<&mut Self> {
+        self.options.set(key, value)?;
+        Ok(self)
+    }
+
+    /// Set multiple codec options at once.
+    pub fn options(&mut self, options: &Options) -> Result<&mut Self> {
+        self.options.extend(options)?;
+        Ok(self)
+    }
+
     pub fn open(&self, format: OutputFormat) -> Result<Encoder> {
         unsafe {
-            let sample_rate = self.sample_rate.unwrap_or(44100) as c_int;
-            let sample_format = self.sample_format.unwrap_or(AV_SAMPLE_FMT_S16);
-            let channel_layout = self.channel_layout.unwrap_or(CHANNEL_LAYOUT_STEREO);
-
             LibAV::init();
 
+            let sample_rate = self.select_sample_rate()?;
+            let sample_format = self.select_sample_format()?;
+            let channel_layout = self.select_channel_layout()?;
+            self.validate(sample_rate)?;
+
             let mut codec_context = avcodec_alloc_context3(self.codec.as_ptr());
             if codec_context.is_null() {
-                bail!("Could not allocate an encoding context");
+                bail!(ErrorKind::AllocFailed("encoding context"));
             }
 
             // Initialize encoder fields
             common::encoder::init(codec_context, format);
             (*codec_context).sample_rate = sample_rate;
-            (*codec_context).sample_fmt = sample_format;
+            (*codec_context).sample_fmt = sample_format.as_raw();
             (*codec_context).time_base = AVRational { num: 1, den: sample_rate };
             (*codec_context).channel_layout = channel_layout.bits();
             (*codec_context).channels = av_get_channel_layout_nb_channels(channel_layout.bits());
+            if let Some(bitrate) = self.bitrate {
+                (*codec_context).bit_rate = bitrate;
+            }
+            if let Some(compression_level) = self.compression_level {
+                (*codec_context).compression_level = compression_level as c_int;
+            }
+            if let Some(cutoff) = self.cutoff {
+                (*codec_context).cutoff = cutoff as c_int;
+            }
+            if let Some(profile) = self.profile {
+                (*codec_context).profile = profile;
+            }
 
-            common::encoder::open(codec_context, "audio")?;
+            let unused_options = common::encoder::open(codec_context, "audio", &self.options)?;
+
+            // Encoders with a fixed frame size get their input through a fifo
+            let variable_frame_size = self.codec.capabilities().contains(CAP_VARIABLE_FRAME_SIZE);
+            let fifo = if (*codec_context).frame_size > 0 && !variable_frame_size {
+                match AudioFifo::new(sample_rate as u32, sample_format, channel_layout) {
+                    Ok(fifo) => Some(fifo),
+                    Err(e) => {
+                        avcodec_free_context(&mut codec_context);
+                        return Err(e);
+                    },
+                }
+            } else {
+                None
+            };
 
             Ok(Encoder {
                 ptr: codec_context,
+                resampler: Resampler::new(),
+                fifo: fifo,
+                pending_packets: VecDeque::new(),
+                unused_options: unused_options,
             })
         }
     }
 }
 
+impl EncoderBuilder {
+    /// The requested sample rate if the codec supports it.
+    /// Otherwise 48000 or 44100 Hz, or the first one supported by the codec.
+    fn select_sample_rate(&self) -> Result<c_int> {
+        let supported = self.codec.sample_rates();
+
+        match self.sample_rate {
+            Some(rate) => {
+                let rate = rate as c_int;
+                if !supported.is_empty() && !supported.contains(&rate) {
+                    common::encoder::invalid_setting("sample_rate", format!(
+                        "{} does not support {} Hz (supported: {:?})",
+                        self.codec.name().unwrap_or_default().to_string_lossy(), rate, supported,
+                    ))?;
+                }
+                Ok(rate)
+            },
+            None if supported.is_empty() => Ok(44100),
+            None => Ok([48000, 44100].iter()
+                .find(|rate| supported.contains(rate))
+                .cloned()
+                .unwrap_or(supported[0])),
+        }
+    }
+
+    /// The requested sample format if the codec supports it.
+    /// Otherwise the codec's preferred format.
+    fn select_sample_format(&self) -> Result<SampleFormat> {
+        let supported = self.codec.sample_formats();
+
+        match self.sample_format {
+            Some(format) => {
+                if !supported.is_empty() && !supported.contains(&format) {
+                    common::encoder::invalid_setting("sample_format", format!(
+                        "{} does not support {} (supported: {})",
+                        self.codec.name().unwrap_or_default().to_string_lossy(),
+                        format,
+                        supported.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(", "),
+                    ))?;
+                }
+                Ok(format)
+            },
+            None => Ok(supported.first().cloned().unwrap_or(SampleFormat::S16)),
+        }
+    }
+
+    /// The requested channel layout if the codec supports it.
+    /// Otherwise stereo, or the first one supported by the codec.
+    fn select_channel_layout(&self) -> Result<ChannelLayout> {
+        let supported = self.codec.channel_layouts();
+
+        match self.channel_layout {
+            Some(layout) => {
+                if !supported.is_empty() && !supported.contains(&layout.bits()) {
+                    common::encoder::invalid_setting("channel_layout", format!(
+                        "{} does not support {:?} (supported: {:?})",
+                        self.codec.name().unwrap_or_default().to_string_lossy(),
+                        layout,
+                        supported.iter().map(|&bits| ChannelLayout::from_bits_truncate(bits)).collect::<Vec<_>>(),
+                    ))?;
+                }
+                Ok(layout)
+            },
+            None if supported.is_empty() || supported.contains(&CHANNEL_LAYOUT_STEREO.bits()) => Ok(CHANNEL_LAYOUT_STEREO),
+            None => Ok(ChannelLayout::from_bits_truncate(supported[0])),
+        }
+    }
+
+    fn validate(&self, sample_rate: c_int) -> Result<()> {
+        if self.bitrate.map_or(false, |bitrate| bitrate <= 0) {
+            common::encoder::invalid_setting("bitrate", "must be positive")?;
+        }
+        if self.compression_level.map_or(false, |level| level > c_int::max_value() as u32) {
+            common::encoder::invalid_setting("compression_level", format!("must not exceed {}", c_int::max_value()))?;
+        }
+        if let Some(cutoff) = self.cutoff {
+            if cutoff == 0 || cutoff > (sample_rate / 2) as u32 {
+                common::encoder::invalid_setting("cutoff", format!("must be between 1 and {} Hz (half the sample rate)", sample_rate / 2))?;
+            }
+        }
+        if let Some(profile) = self.profile {
+            common::encoder::require_supported_profile(self.codec, profile)?;
+        }
+
+        Ok(())
+    }
+}
+
 pub struct Packets<'encoder> {
     encoder: OwnedOrRefMut<'encoder, Encoder>,
 }
@@ -222,22 +480,14 @@ impl<'encoder> Iterator for Packets<'encoder> {
     type Item = Result<Packet<'static>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            let mut packet = ffi::av_packet_alloc();
-            let res = ffi::avcodec_receive_packet(self.encoder.as_mut_ptr(), packet);
-
-            if res < 0 {
-                ffi::av_packet_free(&mut packet);
-
-                match res {
-                    ffi::AVERROR_EAGAIN | ffi::AVERROR_EOF => return None,
-                    _ => return Some(Err(format!("Failed to receive packet: 0x{:X}", res).into())),
-                }
-            }
-
-            let packet = Packet::from_ptr(packet, self.encoder.time_base());
+        if let Some(packet) = self.encoder.pending_packets.pop_front() {
+            return Some(Ok(packet));
+        }
 
-            Some(Ok(packet))
+        match self.encoder.receive_packet() {
+            Ok(Some(packet)) => Some(Ok(packet)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
         }
     }
 }
@@ -0,0 +1,168 @@
+use std::os::raw::{c_int, c_void};
+use ffi;
+use audio::{ChannelLayout, SampleFormat};
+use errors::*;
+use super::Frame;
+
+/// A buffer of audio samples for cutting frames of any length
+/// into frames of a fixed size (e.g. the frame size of an encoder).
+///
+/// Timestamps are counted in samples, i.e. in a time base of `1/sample_rate`.
+/// The pts of the first sample pushed into an empty fifo is carried over
+/// to the frames taken out of it.
+pub struct AudioFifo {
+    ptr: *mut ffi::AVAudioFifo,
+    sample_rate: u32,
+    sample_format: SampleFormat,
+    channel_layout: ChannelLayout,
+    next_pts: i64,
+}
+
+unsafe impl Send for AudioFifo {}
+unsafe impl Sync for AudioFifo {}
+
+impl AudioFifo {
+    pub fn new(sample_rate: u32, sample_format: SampleFormat, channel_layout: ChannelLayout) -> Result<Self> {
+        unsafe {
+            let num_channels = ffi::av_get_channel_layout_nb_channels(channel_layout.bits());
+            // The fifo grows as needed
+            let initial_size = 1;
+            let ptr = ffi::av_audio_fifo_alloc(sample_format.as_raw(), num_channels, initial_size);
+            if ptr.is_null() {
+                bail!(ErrorKind::AllocFailed("audio fifo"));
+            }
+
+            Ok(AudioFifo {
+                ptr: ptr,
+                sample_rate: sample_rate,
+                sample_format: sample_format,
+                channel_layout: channel_layout,
+                next_pts: ffi::NOPTS_VALUE,
+            })
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        self.channel_layout
+    }
+
+    /// The number of buffered samples.
+    pub fn len(&self) -> usize {
+        unsafe {
+            ffi::av_audio_fifo_size(self.ptr) as usize
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append the samples of `frame`.
+    ///
+    /// The frame must match the fifo's sample rate, format and channel layout.
+    pub fn push(&mut self, frame: &Frame) -> Result<()> {
+        unsafe {
+            if frame.sample_rate() != self.sample_rate
+            || frame.sample_format() != self.sample_format
+            || frame.channel_layout() != self.channel_layout {
+                bail!("Audio frame does not match the format of the audio fifo");
+            }
+
+            if frame.num_samples() == 0 {
+                return Ok(());
+            }
+
+            if self.is_empty() {
+                self.next_pts = frame.pts();
+            }
+
+            let data = frame.as_ref().extended_data as *mut *mut c_void;
+            let num_samples = frame.num_samples() as c_int;
+            let res = ffi::av_audio_fifo_write(self.ptr, data, num_samples);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not write to audio fifo"));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Take a frame of exactly `num_samples` samples.
+    ///
+    /// Returns `None` if fewer samples are buffered.
+    pub fn pop(&mut self, num_samples: usize) -> Result<Option<Frame>> {
+        if num_samples == 0 || self.len() < num_samples {
+            return Ok(None);
+        }
+
+        self.read(num_samples, num_samples).map(Some)
+    }
+
+    /// Take all remaining samples (at most `num_samples`)
+    /// as a frame of `num_samples` samples, padded with silence.
+    ///
+    /// Returns `None` if the fifo is empty.
+    pub fn pop_padded(&mut self, num_samples: usize) -> Result<Option<Frame>> {
+        if num_samples == 0 || self.is_empty() {
+            return Ok(None);
+        }
+
+        let available = self.len().min(num_samples);
+        self.read(available, num_samples).map(Some)
+    }
+
+    /// Take all remaining samples as a single frame.
+    ///
+    /// Returns `None` if the fifo is empty.
+    pub fn pop_remaining(&mut self) -> Result<Option<Frame>> {
+        let len = self.len();
+        self.pop(len)
+    }
+
+    /// Read `num_samples` samples into a frame of `frame_size` samples.
+    fn read(&mut self, num_samples: usize, frame_size: usize) -> Result<Frame> {
+        unsafe {
+            let mut frame = Frame::new(frame_size, self.sample_rate, self.sample_format, self.channel_layout)?;
+
+            let data = frame.as_ref().extended_data as *mut *mut c_void;
+            let res = ffi::av_audio_fifo_read(self.ptr, data, num_samples as c_int);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not read from audio fifo"));
+            }
+
+            if frame_size > num_samples {
+                let num_channels = frame.num_channels() as c_int;
+                ffi::av_samples_set_silence(
+                    frame.as_ref().extended_data,
+                    num_samples as c_int,
+                    (frame_size - num_samples) as c_int,
+                    num_channels,
+                    self.sample_format.as_raw(),
+                );
+            }
+
+            frame.set_pts(self.next_pts);
+            if self.next_pts != ffi::NOPTS_VALUE {
+                self.next_pts += num_samples as i64;
+            }
+
+            Ok(frame)
+        }
+    }
+}
+
+impl Drop for AudioFifo {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::av_audio_fifo_free(self.ptr);
+        }
+    }
+}
@@ -1,10 +1,9 @@
 use std::slice;
 use std::os::raw::c_int;
-use audio::ChannelLayout;
+use audio::{self, ChannelLayout, SampleFormat};
 use ffi::{
     self,
     AVFrame,
-    AVSampleFormat,
     av_frame_alloc,
     av_frame_get_buffer,
     av_frame_free,
@@ -12,11 +11,12 @@ use ffi::{
     av_sample_fmt_is_planar,
     AV_NUM_DATA_POINTERS,
 };
+use common::{SideData, SideDataMut};
 use errors::*;
 
 pub struct Frame {
     ptr: *mut AVFrame,
-    sample_format: AVSampleFormat,
+    sample_format: SampleFormat,
 }
 
 // See https://github.com/panicbit/rust-av/issues/28
@@ -25,16 +25,16 @@ unsafe impl Sync for Frame {}
 
 impl Frame {
     /// TODO: Check for overflows
-    pub fn new(num_samples: usize, sample_rate: u32, sample_format: AVSampleFormat, channel_layout: ChannelLayout) -> Result<Self> {
+    pub fn new(num_samples: usize, sample_rate: u32, sample_format: SampleFormat, channel_layout: ChannelLayout) -> Result<Self> {
         unsafe {
 
             let mut frame = av_frame_alloc();
             if frame.is_null() {
-                bail!("Could not allocate frame");
+                bail!(ErrorKind::AllocFailed("audio frame"));
             }
 
             (*frame).pts = 0;
-            (*frame).format = sample_format as c_int;
+            (*frame).format = sample_format.as_raw() as c_int;
             (*frame).channel_layout = channel_layout.bits();
             (*frame).sample_rate = sample_rate as i32;
             (*frame).nb_samples = num_samples as i32;
@@ -44,7 +44,7 @@ impl Frame {
                 let res = av_frame_get_buffer(frame, align);
                 if res < 0 {
                     av_frame_free(&mut frame);
-                    bail!("Could not allocate audio frame buffer: 0x{:X}", res);
+                    bail!(ErrorKind::av(res, "Could not allocate audio frame buffer"));
                 }
             }
 
@@ -55,6 +55,19 @@ impl Frame {
         }
     }
 
+    pub fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+
+    /// TODO: Check for underflow
+    pub fn sample_rate(&self) -> u32 {
+        self.as_ref().sample_rate as u32
+    }
+
+    pub fn channel_layout(&self) -> ChannelLayout {
+        ChannelLayout::from_bits_truncate(self.as_ref().channel_layout)
+    }
+
     pub fn num_channels(&self) -> usize {
         unsafe {
             av_get_channel_layout_nb_channels(self.as_ref().channel_layout) as usize
@@ -63,15 +76,15 @@ impl Frame {
 
     pub fn is_planar(&self) -> bool {
         unsafe {
-            av_sample_fmt_is_planar(self.sample_format) != 0
+            av_sample_fmt_is_planar(self.sample_format.as_raw()) != 0
         }
     }
 
-    pub fn data_mut(&mut self) -> [&mut [u8]; AV_NUM_DATA_POINTERS as usize] {
+    /// Mutable access to the sample data.
+    /// The frame data is copied first if it is shared with other frames.
+    pub fn data_mut(&mut self) -> Result<[&mut [u8]; AV_NUM_DATA_POINTERS as usize]> {
         unsafe {
-            if ffi::av_frame_make_writable(self.ptr) < 0 {
-                panic!("av_frame_make_writable failed (OOM?)");
-            }
+            self.make_writable()?;
 
             // For audio only linesize[0] is set. Every channel needs to have the same size.
             let buf_len = self.as_ref().linesize[0] as usize;
@@ -86,7 +99,7 @@ impl Frame {
                 channels[i] = slice::from_raw_parts_mut(self.as_ref().data[i], buf_len);
             }
 
-            channels
+            Ok(channels)
         }
     }
 
@@ -94,13 +107,74 @@ impl Frame {
         self.as_ref().nb_samples as usize
     }
 
+    pub fn pts(&self) -> i64 {
+        self.as_ref().pts
+    }
+
     pub fn set_pts(&mut self, pts: i64) {
         self.as_mut().pts = pts;
     }
+
+    /// Additional data attached to the frame, e.g. ReplayGain values.
+    pub fn side_data(&self) -> SideData {
+        unsafe { SideData::from_ptr(self.ptr) }
+    }
+
+    pub fn side_data_mut(&mut self) -> SideDataMut {
+        unsafe { SideDataMut::from_ptr(self.ptr) }
+    }
+
+    pub fn is_compatible_with_encoder(&self, encoder: &audio::Encoder) -> bool {
+           self.sample_format() == encoder.sample_format()
+        && self.sample_rate() == encoder.sample_rate()
+        && self.channel_layout() == encoder.channel_layout()
+    }
 }
 
 impl Frame {
-    pub unsafe fn from_ptr(ptr: *mut AVFrame, sample_format: AVSampleFormat) -> Self {
+    /// Whether the frame data is not shared with other frames.
+    pub fn is_writable(&self) -> bool {
+        unsafe { ffi::av_frame_is_writable(self.ptr) != 0 }
+    }
+
+    /// Make sure the frame data is not shared with other frames,
+    /// copying it if necessary.
+    pub fn make_writable(&mut self) -> Result<()> {
+        unsafe {
+            let res = ffi::av_frame_make_writable(self.ptr);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not make audio frame writable"));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Create a frame with its own copy of the data.
+    /// Unlike `clone`, the data is not shared.
+    pub fn copy(&self) -> Result<Self> {
+        unsafe {
+            let frame = Frame::new(self.num_samples(), self.sample_rate(), self.sample_format(), self.channel_layout())?;
+
+            if self.num_samples() > 0 {
+                let res = ffi::av_frame_copy(frame.ptr, self.ptr);
+                if res < 0 {
+                    bail!(ErrorKind::av(res, "Could not copy audio frame data"));
+                }
+            }
+
+            let res = ffi::av_frame_copy_props(frame.ptr, self.ptr);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not copy audio frame properties"));
+            }
+
+            Ok(frame)
+        }
+    }
+}
+
+impl Frame {
+    pub unsafe fn from_ptr(ptr: *mut AVFrame, sample_format: SampleFormat) -> Self {
         Frame {
             ptr: ptr,
             sample_format: sample_format,
@@ -115,7 +189,43 @@ impl Frame {
         unsafe { &mut *self.ptr }
     }
 
+    pub fn as_ptr(&self) -> *const AVFrame {
+        self.ptr
+    }
+
     pub fn as_mut_ptr(&mut self) -> *mut AVFrame {
         self.ptr
     }
 }
+
+/// Creates a new reference to the same frame data.
+///
+/// # Panics
+///
+/// Panics if the frame can't be allocated (OOM).
+impl Clone for Frame {
+    fn clone(&self) -> Self {
+        unsafe {
+            let mut frame = av_frame_alloc();
+            if frame.is_null() {
+                panic!("Could not allocate audio frame (OOM?)");
+            }
+
+            let res = ffi::av_frame_ref(frame, self.ptr);
+            if res < 0 {
+                av_frame_free(&mut frame);
+                panic!("Could not reference audio frame: {}", av_strerror(res));
+            }
+
+            Self::from_ptr(frame, self.sample_format)
+        }
+    }
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::av_frame_free(&mut self.ptr);
+        }
+    }
+}
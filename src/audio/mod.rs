@@ -2,6 +2,9 @@
 pub mod constants;
 pub use self::constants::ChannelLayout;
 
+mod sample_format;
+pub use self::sample_format::SampleFormat;
+
 mod encoder;
 pub use self::encoder::{
     Encoder,
@@ -17,3 +20,9 @@ pub use self::decoder::{
 
 mod frame;
 pub use self::frame::Frame;
+
+mod resampler;
+pub use self::resampler::Resampler;
+
+mod fifo;
+pub use self::fifo::AudioFifo;
@@ -0,0 +1,219 @@
+use std::ptr;
+use ffi;
+use audio::{ChannelLayout, SampleFormat};
+use errors::*;
+use super::Frame;
+
+/// A context for resampling/converting audio frames.
+///
+/// Resampling is most efficiently done by reusing the resampler
+/// for a specific combination of source/target sample rate, format and channel layout.
+/// It's allowed to use different values for each invocation of the resampling
+/// functions, but it will result in reallocation of the internal resampling context,
+/// which drops any samples still buffered in it.
+pub struct Resampler {
+    context: Option<SwrContext>,
+    src_rate: u32,
+    src_fmt: SampleFormat,
+    src_layout: ChannelLayout,
+    dst_rate: u32,
+    dst_fmt: SampleFormat,
+    dst_layout: ChannelLayout,
+}
+
+unsafe impl Send for Resampler{}
+unsafe impl Sync for Resampler{}
+
+impl Resampler {
+    /// Create a new resampling context.
+    pub fn new() -> Self {
+        Resampler {
+            context: None,
+            src_rate: 0,
+            src_fmt: SampleFormat::None,
+            src_layout: ChannelLayout::empty(),
+            dst_rate: 0,
+            dst_fmt: SampleFormat::None,
+            dst_layout: ChannelLayout::empty(),
+        }
+    }
+
+    /// Actually initialize the context and
+    /// reinitialize it if needed.
+    fn init_context(&mut self,
+        src_rate: u32, src_fmt: SampleFormat, src_layout: ChannelLayout,
+        dst_rate: u32, dst_fmt: SampleFormat, dst_layout: ChannelLayout,
+    ) -> Result<&mut SwrContext> {
+        // (Re)allocate
+        if    self.context.is_none()
+           || self.src_rate   != src_rate   || self.dst_rate   != dst_rate
+           || self.src_fmt    != src_fmt    || self.dst_fmt    != dst_fmt
+           || self.src_layout != src_layout || self.dst_layout != dst_layout
+        {
+            self.context = Some(SwrContext::new(
+                src_rate, src_fmt, src_layout,
+                dst_rate, dst_fmt, dst_layout,
+            )?);
+
+            self.src_rate   = src_rate;
+            self.src_fmt    = src_fmt;
+            self.src_layout = src_layout;
+            self.dst_rate   = dst_rate;
+            self.dst_fmt    = dst_fmt;
+            self.dst_layout = dst_layout;
+        }
+
+        Ok(self.context.as_mut().unwrap())
+    }
+
+    /// Convert `src` to the given sample rate, format and channel layout.
+    ///
+    /// The returned frame may contain fewer (or more) samples than expected,
+    /// because the resampler buffers samples internally.
+    /// Use `flush` to retrieve the remaining samples once all input has been resampled.
+    pub fn resample_frame(&mut self, src: &Frame,
+        dst_rate: u32, dst_fmt: SampleFormat, dst_layout: ChannelLayout,
+    ) -> Result<Frame> {
+        unsafe {
+            let src_rate   = src.sample_rate();
+            let src_fmt    = src.sample_format();
+            let src_layout = src.channel_layout();
+
+            let context = self.init_context(
+                src_rate, src_fmt, src_layout,
+                dst_rate, dst_fmt, dst_layout,
+            )?;
+
+            let mut dst = Frame::new(0, dst_rate, dst_fmt, dst_layout)?;
+
+            // Convert the pts to the target sample rate, taking buffered samples into account
+            if src.pts() != ffi::NOPTS_VALUE {
+                let pts = src.pts() * dst_rate as i64;
+                let pts = ffi::swr_next_pts(context.as_mut_ptr(), pts);
+                dst.set_pts(pts / src_rate as i64);
+            } else {
+                dst.set_pts(ffi::NOPTS_VALUE);
+            }
+
+            let res = ffi::swr_convert_frame(context.as_mut_ptr(), dst.as_mut_ptr(), src.as_ptr());
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not resample frame"));
+            }
+
+            Ok(dst)
+        }
+    }
+
+    /// Drain the samples still buffered in the resampler.
+    ///
+    /// Returns `None` if there are no remaining samples.
+    pub fn flush(&mut self) -> Result<Option<Frame>> {
+        unsafe {
+            let dst_rate   = self.dst_rate;
+            let dst_fmt    = self.dst_fmt;
+            let dst_layout = self.dst_layout;
+
+            let context = match self.context.as_mut() {
+                Some(context) => context,
+                None => return Ok(None),
+            };
+
+            if ffi::swr_get_delay(context.as_mut_ptr(), dst_rate as i64) <= 0 {
+                return Ok(None);
+            }
+
+            let mut dst = Frame::new(0, dst_rate, dst_fmt, dst_layout)?;
+            dst.set_pts(ffi::NOPTS_VALUE);
+
+            let res = ffi::swr_convert_frame(context.as_mut_ptr(), dst.as_mut_ptr(), ptr::null());
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not flush resampler"));
+            }
+
+            if dst.num_samples() == 0 {
+                return Ok(None);
+            }
+
+            Ok(Some(dst))
+        }
+    }
+
+    /// The number of samples (at the target sample rate)
+    /// currently buffered in the resampler.
+    pub fn delay(&mut self) -> usize {
+        unsafe {
+            let dst_rate = self.dst_rate;
+            match self.context.as_mut() {
+                Some(context) => {
+                    let delay = ffi::swr_get_delay(context.as_mut_ptr(), dst_rate as i64);
+                    // A negative delay is an error code, which can only mean nothing is buffered
+                    if delay < 0 { 0 } else { delay as usize }
+                },
+                None => 0,
+            }
+        }
+    }
+}
+
+struct SwrContext(*mut ffi::SwrContext);
+
+impl SwrContext {
+    fn new(
+        source_rate: u32, source_format: SampleFormat, source_layout: ChannelLayout,
+        target_rate: u32, target_format: SampleFormat, target_layout: ChannelLayout,
+    ) -> Result<Self> {
+        unsafe {
+            let log_offset = 0;
+            let log_ctx = ptr::null_mut();
+
+            // Check that the parameters are valid
+            {
+                if source_rate == 0 || source_layout.is_empty() {
+                    bail!("Resample source rate {} / channel layout 0x{:X} invalid", source_rate, source_layout.bits())
+                }
+
+                if target_rate == 0 || target_layout.is_empty() {
+                    bail!("Resample target rate {} / channel layout 0x{:X} invalid", target_rate, target_layout.bits())
+                }
+            }
+
+            let mut resampler = ffi::swr_alloc_set_opts(
+                ptr::null_mut(),
+                target_layout.bits() as i64, target_format.as_raw(), target_rate as i32,
+                source_layout.bits() as i64, source_format.as_raw(), source_rate as i32,
+                log_offset,
+                log_ctx
+            );
+
+            if resampler.is_null() {
+                bail!(ErrorKind::AllocFailed("resampler context"));
+            }
+
+            let res = ffi::swr_init(resampler);
+            if res < 0 {
+                ffi::swr_free(&mut resampler);
+                bail!(ErrorKind::av(res, "Could not initialize resampler context"));
+            }
+
+            Ok(SwrContext(resampler))
+        }
+    }
+
+    unsafe fn free(&mut self) {
+        if !self.0.is_null() {
+            ffi::swr_free(&mut self.0);
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut ffi::SwrContext {
+        self.0
+    }
+}
+
+impl Drop for SwrContext {
+    fn drop(&mut self) {
+        unsafe {
+            self.free()
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::str::FromStr;
+use std::ffi::CString;
+use ffi;
+use util::AsCStr;
+use errors::*;
+
+raw_enum! {
+    pub enum SampleFormat(AVSampleFormat) {
+        None = AV_SAMPLE_FMT_NONE,
+        U8   = AV_SAMPLE_FMT_U8,
+        S16  = AV_SAMPLE_FMT_S16,
+        S32  = AV_SAMPLE_FMT_S32,
+        Flt  = AV_SAMPLE_FMT_FLT,
+        Dbl  = AV_SAMPLE_FMT_DBL,
+        U8p  = AV_SAMPLE_FMT_U8P,
+        S16p = AV_SAMPLE_FMT_S16P,
+        S32p = AV_SAMPLE_FMT_S32P,
+        Fltp = AV_SAMPLE_FMT_FLTP,
+        Dblp = AV_SAMPLE_FMT_DBLP,
+    }
+}
+
+impl SampleFormat {
+    /// The libav name of this format, e.g. `fltp`.
+    pub fn name(self) -> Option<&'static str> {
+        unsafe {
+            ffi::av_get_sample_fmt_name(self.as_raw()).as_cstr()
+                .and_then(|name| name.to_str().ok())
+        }
+    }
+
+    /// Whether each channel is stored in a separate plane.
+    pub fn is_planar(self) -> bool {
+        unsafe { ffi::av_sample_fmt_is_planar(self.as_raw()) != 0 }
+    }
+
+    /// The size of a single sample of a single channel in bytes.
+    pub fn bytes_per_sample(self) -> usize {
+        unsafe { ffi::av_get_bytes_per_sample(self.as_raw()).max(0) as usize }
+    }
+
+    /// The interleaved variant of this format (e.g. `Flt` for `Fltp`).
+    pub fn packed(self) -> Self {
+        unsafe { SampleFormat::from_raw(ffi::av_get_packed_sample_fmt(self.as_raw())) }
+    }
+
+    /// The planar variant of this format (e.g. `Fltp` for `Flt`).
+    pub fn planar(self) -> Self {
+        unsafe { SampleFormat::from_raw(ffi::av_get_planar_sample_fmt(self.as_raw())) }
+    }
+}
+
+impl fmt::Display for SampleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name().unwrap_or("none"))
+    }
+}
+
+impl FromStr for SampleFormat {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        unsafe {
+            let c_name = CString::new(name)
+                .map_err(|_| format!("Invalid sample format name {:?}", name))?;
+
+            match ffi::av_get_sample_fmt(c_name.as_ptr()) {
+                ffi::AVSampleFormat::AV_SAMPLE_FMT_NONE if name != "none" => bail!("Unknown sample format {:?}", name),
+                format => Ok(SampleFormat::from_raw(format)),
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use ffi::{
+    AV_CODEC_CAP_DRAW_HORIZ_BAND,
+    AV_CODEC_CAP_DR1,
+    AV_CODEC_CAP_TRUNCATED,
+    AV_CODEC_CAP_DELAY,
+    AV_CODEC_CAP_SMALL_LAST_FRAME,
+    AV_CODEC_CAP_SUBFRAMES,
+    AV_CODEC_CAP_EXPERIMENTAL,
+    AV_CODEC_CAP_CHANNEL_CONF,
+    AV_CODEC_CAP_FRAME_THREADS,
+    AV_CODEC_CAP_SLICE_THREADS,
+    AV_CODEC_CAP_PARAM_CHANGE,
+    AV_CODEC_CAP_AUTO_THREADS,
+    AV_CODEC_CAP_VARIABLE_FRAME_SIZE,
+    AV_CODEC_CAP_INTRA_ONLY,
+    AV_CODEC_CAP_LOSSLESS,
+};
+
+bitflags! {
+    pub flags Capabilities: u32 {
+        /// The decoder can use `draw_horiz_band`.
+        const CAP_DRAW_HORIZ_BAND     = AV_CODEC_CAP_DRAW_HORIZ_BAND     as u32,
+        /// The codec uses `get_buffer` for allocating buffers.
+        const CAP_DR1                 = AV_CODEC_CAP_DR1                 as u32,
+        /// The decoder can handle truncated bitstreams.
+        const CAP_TRUNCATED           = AV_CODEC_CAP_TRUNCATED           as u32,
+        /// The codec has a delay and needs to be flushed at the end.
+        const CAP_DELAY               = AV_CODEC_CAP_DELAY               as u32,
+        /// The encoder accepts a smaller last frame.
+        const CAP_SMALL_LAST_FRAME    = AV_CODEC_CAP_SMALL_LAST_FRAME    as u32,
+        /// The decoder can output multiple frames per packet.
+        const CAP_SUBFRAMES           = AV_CODEC_CAP_SUBFRAMES           as u32,
+        /// The codec is experimental and has to be enabled explicitly.
+        const CAP_EXPERIMENTAL        = AV_CODEC_CAP_EXPERIMENTAL        as u32,
+        /// The decoder determines the channel configuration itself.
+        const CAP_CHANNEL_CONF        = AV_CODEC_CAP_CHANNEL_CONF        as u32,
+        /// The codec supports frame threading.
+        const CAP_FRAME_THREADS       = AV_CODEC_CAP_FRAME_THREADS       as u32,
+        /// The codec supports slice threading.
+        const CAP_SLICE_THREADS       = AV_CODEC_CAP_SLICE_THREADS       as u32,
+        /// The codec supports parameter changes at any point.
+        const CAP_PARAM_CHANGE        = AV_CODEC_CAP_PARAM_CHANGE        as u32,
+        /// The codec manages its threads itself.
+        const CAP_AUTO_THREADS        = AV_CODEC_CAP_AUTO_THREADS        as u32,
+        /// The audio encoder accepts frames of any size.
+        const CAP_VARIABLE_FRAME_SIZE = AV_CODEC_CAP_VARIABLE_FRAME_SIZE as u32,
+        /// The codec only produces/consumes intra frames.
+        const CAP_INTRA_ONLY          = AV_CODEC_CAP_INTRA_ONLY          as u32,
+        /// The encoder is lossless.
+        const CAP_LOSSLESS            = AV_CODEC_CAP_LOSSLESS            as u32,
+    }
+}
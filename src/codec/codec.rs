@@ -1,21 +1,30 @@
 use std::fmt;
+use std::ptr;
 use std::slice;
 use std::ffi::{CString, CStr};
+use std::os::raw::c_int;
 use LibAV;
 use ffi::{
+    self,
     AVCodec,
     AVCodecID,
-    AVPixelFormat,
+    AVRational,
+    av_codec_next,
     avcodec_find_encoder_by_name,
     avcodec_find_encoder,
     avcodec_find_decoder,
     av_codec_is_encoder,
     av_codec_is_decoder,
 };
-use codec::MediaType;
+use codec::{CodecId, MediaType};
+use video::PixelFormat;
+use audio::SampleFormat;
 use super::{
+    Capabilities,
     Descriptor,
     DescriptorIter,
+    PrivateOptionIter,
+    ProfileIter,
 };
 use util::AsCStr;
 
@@ -39,25 +48,23 @@ impl Codec {
         }
     }
 
-    pub fn find_encoder_by_id(codec_id: AVCodecID) -> Result<Self> {
+    pub fn find_encoder_by_id(codec_id: CodecId) -> Result<Self> {
         unsafe {
             LibAV::init();
-            let codec = avcodec_find_encoder(codec_id);
+            let codec = avcodec_find_encoder(codec_id.as_raw());
             if codec.is_null() {
-                // TODO: maybe use avcodec_get_name(codec_id)
-                bail!(ErrorKind::EncoderNotFound(format!("{:?}", codec_id)))
+                bail!(ErrorKind::EncoderNotFound(codec_id.to_string()))
             }
             Ok(Self::from_ptr(codec))
         }
     }
 
-    pub fn find_decoder_by_id(codec_id: AVCodecID) -> Result<Self> {
+    pub fn find_decoder_by_id(codec_id: CodecId) -> Result<Self> {
         unsafe {
             LibAV::init();
-            let codec = avcodec_find_decoder(codec_id);
+            let codec = avcodec_find_decoder(codec_id.as_raw());
             if codec.is_null() {
-                // TODO: maybe use avcodec_get_name(codec_id)
-                bail!(ErrorKind::DecoderNotFound(format!("{:?}", codec_id)))
+                bail!(ErrorKind::DecoderNotFound(codec_id.to_string()))
             }
             Ok(Self::from_ptr(codec))
         }
@@ -71,41 +78,94 @@ impl Codec {
         unsafe { av_codec_is_decoder(self.ptr) != 0 }
     }
 
-    pub fn id(&self) -> AVCodecID {
-        self.as_ref().id
+    pub fn id(&self) -> CodecId {
+        CodecId::from_raw(self.as_ref().id)
     }
 
-    pub fn name(&self) -> &CStr {
-        unsafe { self.as_ref().name.as_cstr().unwrap() }
+    pub fn name(&self) -> Option<&CStr> {
+        unsafe { self.as_ref().name.as_cstr() }
     }
 
-    pub fn long_name(&self) -> &CStr {
-        unsafe { self.as_ref().name.as_cstr().unwrap() }
+    /// The descriptive name. `None` if libav was built with `--enable-small`.
+    pub fn long_name(&self) -> Option<&CStr> {
+        unsafe { self.as_ref().long_name.as_cstr() }
     }
 
     pub fn media_type(&self) -> MediaType {
         MediaType::from_raw(self.as_ref().type_)
     }
 
-    pub fn pixel_formats(&self) -> &[AVPixelFormat] {
+    /// The pixel formats supported by this codec.
+    /// Empty if unknown.
+    pub fn pixel_formats(&self) -> Vec<PixelFormat> {
         unsafe {
-            use ffi::AVPixelFormat::AV_PIX_FMT_NONE;
+            terminated_slice(self.as_ref().pix_fmts, |&fmt| fmt == ffi::AVPixelFormat::AV_PIX_FMT_NONE)
+                .iter().map(|&fmt| PixelFormat::from_raw(fmt)).collect()
+        }
+    }
+
+    /// The sample formats supported by this codec.
+    /// Empty if unknown.
+    pub fn sample_formats(&self) -> Vec<SampleFormat> {
+        unsafe {
+            terminated_slice(self.as_ref().sample_fmts, |&fmt| fmt == ffi::AVSampleFormat::AV_SAMPLE_FMT_NONE)
+                .iter().map(|&fmt| SampleFormat::from_raw(fmt)).collect()
+        }
+    }
 
-            let pix_fmts = (*self.ptr).pix_fmts;
-            let mut len = 0;
+    /// The sample rates supported by this codec.
+    /// Empty if unknown.
+    pub fn sample_rates(&self) -> &[c_int] {
+        unsafe {
+            terminated_slice(self.as_ref().supported_samplerates, |&rate| rate == 0)
+        }
+    }
 
-            while *pix_fmts.offset(len) != AV_PIX_FMT_NONE {
-                len += 1;
-            }
+    /// The channel layouts (as `ChannelLayout` bits) supported by this codec.
+    /// Empty if unknown.
+    pub fn channel_layouts(&self) -> &[u64] {
+        unsafe {
+            terminated_slice(self.as_ref().channel_layouts, |&layout| layout == 0)
+        }
+    }
 
-            slice::from_raw_parts(pix_fmts, len as usize)
+    /// The frame rates supported by this codec.
+    /// Empty if any frame rate is supported.
+    pub fn frame_rates(&self) -> &[AVRational] {
+        unsafe {
+            terminated_slice(self.as_ref().supported_framerates, |rate| rate.num == 0 && rate.den == 0)
         }
     }
 
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::from_bits_truncate(self.as_ref().capabilities as u32)
+    }
+
+    /// The options specific to this codec implementation,
+    /// including the named values of enum-like options (see `PrivateOption::is_constant`).
+    pub fn private_options(&self) -> PrivateOptionIter {
+        unsafe { PrivateOptionIter::from_class(self.as_ref().priv_class) }
+    }
+
+    /// The profiles this codec implementation recognizes.
+    pub fn profiles(&self) -> ProfileIter {
+        unsafe { ProfileIter::from_ptr(self.as_ref().profiles) }
+    }
+
+    pub fn descriptor(&self) -> Option<Descriptor> {
+        self.id().descriptor()
+    }
+
     pub fn descriptors() -> DescriptorIter {
         LibAV::init();
         DescriptorIter::new()
     }
+
+    /// Every registered encoder and decoder.
+    pub fn all() -> CodecIter {
+        LibAV::init();
+        CodecIter { prev: ptr::null() }
+    }
 }
 
 impl Codec {
@@ -131,17 +191,57 @@ impl fmt::Debug for Codec {
             .field("is_encoder", &self.is_encoder())
             .field("is_decoder", &self.is_decoder())
             .field("media_type", &self.media_type())
+            .field("capabilities", &self.capabilities())
             .field("pixel_formats", &self.pixel_formats())
+            .field("sample_formats", &self.sample_formats())
+            .field("sample_rates", &self.sample_rates())
             .finish()
     }
 }
 
+pub struct CodecIter {
+    prev: *const AVCodec,
+}
+
+impl Iterator for CodecIter {
+    type Item = Codec;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            let next = av_codec_next(self.prev);
+            if next.is_null() {
+                None
+            } else {
+                self.prev = next;
+                Some(Codec::from_ptr(next))
+            }
+        }
+    }
+}
+
+/// A slice of the elements before the first one matching `is_terminator`.
+/// Returns an empty slice if `ptr` is null.
+unsafe fn terminated_slice<'a, T, F>(ptr: *const T, is_terminator: F) -> &'a [T] where
+    F: Fn(&T) -> bool,
+{
+    if ptr.is_null() {
+        return &[];
+    }
+
+    let mut len = 0;
+    while !is_terminator(&*ptr.offset(len)) {
+        len += 1;
+    }
+
+    slice::from_raw_parts(ptr, len as usize)
+}
+
 pub trait AVCodecIDExt {
     fn descriptor(self) -> Option<Descriptor>;
 }
 
 impl AVCodecIDExt for AVCodecID {
     fn descriptor(self) -> Option<Descriptor> {
-        Descriptor::from_codec_id(self)
+        Descriptor::from_codec_id(CodecId::from_raw(self))
     }
 }
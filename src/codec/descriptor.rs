@@ -3,7 +3,6 @@ use std::fmt;
 use std::ptr;
 use LibAV;
 use ffi::{
-    AVCodecID,
     AVCodecDescriptor,
     avcodec_descriptor_get,
     avcodec_descriptor_next,
@@ -11,7 +10,7 @@ use ffi::{
 use super::ProfileIter;
 use super::MimeTypeIter;
 use util::AsCStr;
-use codec::MediaType;
+use codec::{CodecId, MediaType};
 
 #[derive(PartialEq)]
 pub struct Descriptor {
@@ -19,10 +18,10 @@ pub struct Descriptor {
 }
 
 impl Descriptor {
-    pub fn from_codec_id(codec_id: AVCodecID) -> Option<Self> {
+    pub fn from_codec_id(codec_id: CodecId) -> Option<Self> {
         unsafe {
             LibAV::init();
-            let descriptor = avcodec_descriptor_get(codec_id);;
+            let descriptor = avcodec_descriptor_get(codec_id.as_raw());
             if descriptor.is_null() {
                 None
             } else {
@@ -35,8 +34,8 @@ impl Descriptor {
         unsafe { &*self.ptr }
     }
 
-    pub fn id(&self) -> AVCodecID {
-        self.as_ref().id
+    pub fn id(&self) -> CodecId {
+        CodecId::from_raw(self.as_ref().id)
     }
 
     pub fn media_type(&self) -> MediaType {
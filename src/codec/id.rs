@@ -0,0 +1,137 @@
+use std::fmt;
+use std::str::FromStr;
+use std::ffi::{CStr, CString};
+use ffi;
+use codec::{Descriptor, MediaType};
+use errors::*;
+
+raw_enum! {
+    pub enum CodecId(AVCodecID) {
+        None            = AV_CODEC_ID_NONE,
+
+        // Video
+        Mpeg1Video      = AV_CODEC_ID_MPEG1VIDEO,
+        Mpeg2Video      = AV_CODEC_ID_MPEG2VIDEO,
+        H261            = AV_CODEC_ID_H261,
+        H263            = AV_CODEC_ID_H263,
+        Mjpeg           = AV_CODEC_ID_MJPEG,
+        Mpeg4           = AV_CODEC_ID_MPEG4,
+        RawVideo        = AV_CODEC_ID_RAWVIDEO,
+        MsMpeg4v3       = AV_CODEC_ID_MSMPEG4V3,
+        Wmv1            = AV_CODEC_ID_WMV1,
+        Wmv2            = AV_CODEC_ID_WMV2,
+        Wmv3            = AV_CODEC_ID_WMV3,
+        Vc1             = AV_CODEC_ID_VC1,
+        Flv1            = AV_CODEC_ID_FLV1,
+        DvVideo         = AV_CODEC_ID_DVVIDEO,
+        HuffYuv         = AV_CODEC_ID_HUFFYUV,
+        H264            = AV_CODEC_ID_H264,
+        Hevc            = AV_CODEC_ID_HEVC,
+        Theora          = AV_CODEC_ID_THEORA,
+        Vp3             = AV_CODEC_ID_VP3,
+        Vp6             = AV_CODEC_ID_VP6,
+        Vp8             = AV_CODEC_ID_VP8,
+        Vp9             = AV_CODEC_ID_VP9,
+        Png             = AV_CODEC_ID_PNG,
+        Gif             = AV_CODEC_ID_GIF,
+        Bmp             = AV_CODEC_ID_BMP,
+        Tiff            = AV_CODEC_ID_TIFF,
+        Webp            = AV_CODEC_ID_WEBP,
+        Jpeg2000        = AV_CODEC_ID_JPEG2000,
+        Ffv1            = AV_CODEC_ID_FFV1,
+        Dnxhd           = AV_CODEC_ID_DNXHD,
+        ProRes          = AV_CODEC_ID_PRORES,
+        UtVideo         = AV_CODEC_ID_UTVIDEO,
+        Qtrle           = AV_CODEC_ID_QTRLE,
+
+        // Audio
+        // `AV_CODEC_ID_PCM_S16LE` is an alias of `AV_CODEC_ID_FIRST_AUDIO`
+        PcmS16le        = AV_CODEC_ID_FIRST_AUDIO,
+        PcmS16be        = AV_CODEC_ID_PCM_S16BE,
+        PcmU8           = AV_CODEC_ID_PCM_U8,
+        PcmS24le        = AV_CODEC_ID_PCM_S24LE,
+        PcmS32le        = AV_CODEC_ID_PCM_S32LE,
+        PcmF32le        = AV_CODEC_ID_PCM_F32LE,
+        PcmF64le        = AV_CODEC_ID_PCM_F64LE,
+        PcmMulaw        = AV_CODEC_ID_PCM_MULAW,
+        PcmAlaw         = AV_CODEC_ID_PCM_ALAW,
+        AdpcmImaWav     = AV_CODEC_ID_ADPCM_IMA_WAV,
+        AmrNb           = AV_CODEC_ID_AMR_NB,
+        AmrWb           = AV_CODEC_ID_AMR_WB,
+        Mp2             = AV_CODEC_ID_MP2,
+        Mp3             = AV_CODEC_ID_MP3,
+        Aac             = AV_CODEC_ID_AAC,
+        Ac3             = AV_CODEC_ID_AC3,
+        Eac3            = AV_CODEC_ID_EAC3,
+        Dts             = AV_CODEC_ID_DTS,
+        TrueHd          = AV_CODEC_ID_TRUEHD,
+        Vorbis          = AV_CODEC_ID_VORBIS,
+        Opus            = AV_CODEC_ID_OPUS,
+        Flac            = AV_CODEC_ID_FLAC,
+        Alac            = AV_CODEC_ID_ALAC,
+        WmaV1           = AV_CODEC_ID_WMAV1,
+        WmaV2           = AV_CODEC_ID_WMAV2,
+        Speex           = AV_CODEC_ID_SPEEX,
+        Gsm             = AV_CODEC_ID_GSM,
+
+        // Subtitles
+        // `AV_CODEC_ID_DVD_SUBTITLE` is an alias of `AV_CODEC_ID_FIRST_SUBTITLE`
+        DvdSubtitle     = AV_CODEC_ID_FIRST_SUBTITLE,
+        DvbSubtitle     = AV_CODEC_ID_DVB_SUBTITLE,
+        Text            = AV_CODEC_ID_TEXT,
+        Xsub            = AV_CODEC_ID_XSUB,
+        Ssa             = AV_CODEC_ID_SSA,
+        MovText         = AV_CODEC_ID_MOV_TEXT,
+        HdmvPgsSubtitle = AV_CODEC_ID_HDMV_PGS_SUBTITLE,
+        Srt             = AV_CODEC_ID_SRT,
+        Subrip          = AV_CODEC_ID_SUBRIP,
+        WebVtt          = AV_CODEC_ID_WEBVTT,
+        Ass             = AV_CODEC_ID_ASS,
+    }
+}
+
+impl CodecId {
+    /// The libav name of this codec, e.g. `h264`.
+    pub fn name(self) -> &'static str {
+        unsafe {
+            // `avcodec_get_name` never returns null
+            CStr::from_ptr(ffi::avcodec_get_name(self.as_raw()))
+                .to_str()
+                .unwrap_or("unknown_codec")
+        }
+    }
+
+    pub fn media_type(self) -> MediaType {
+        unsafe { MediaType::from_raw(ffi::avcodec_get_type(self.as_raw())) }
+    }
+
+    pub fn descriptor(self) -> Option<Descriptor> {
+        Descriptor::from_codec_id(self)
+    }
+}
+
+impl fmt::Display for CodecId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for CodecId {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        unsafe {
+            ::LibAV::init();
+
+            let c_name = CString::new(name)
+                .map_err(|_| format!("Invalid codec name {:?}", name))?;
+
+            let descriptor = ffi::avcodec_descriptor_get_by_name(c_name.as_ptr());
+            if descriptor.is_null() {
+                bail!("Unknown codec {:?}", name);
+            }
+
+            Ok(CodecId::from_raw((*descriptor).id))
+        }
+    }
+}
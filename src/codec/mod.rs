@@ -1,9 +1,13 @@
 mod codec;
 pub use self::codec::{
     Codec,
+    CodecIter,
     AVCodecIDExt,
 };
 
+mod id;
+pub use self::id::CodecId;
+
 mod descriptor;
 pub use self::descriptor::{
     Descriptor,
@@ -21,3 +25,36 @@ pub use self::mime::MimeTypeIter;
 
 mod media_type;
 pub use self::media_type::MediaType;
+
+mod thread_type;
+pub use self::thread_type::{
+    ThreadType,
+    THREAD_FRAME,
+    THREAD_SLICE,
+};
+
+mod capabilities;
+pub use self::capabilities::{
+    Capabilities,
+    CAP_DRAW_HORIZ_BAND,
+    CAP_DR1,
+    CAP_TRUNCATED,
+    CAP_DELAY,
+    CAP_SMALL_LAST_FRAME,
+    CAP_SUBFRAMES,
+    CAP_EXPERIMENTAL,
+    CAP_CHANNEL_CONF,
+    CAP_FRAME_THREADS,
+    CAP_SLICE_THREADS,
+    CAP_PARAM_CHANGE,
+    CAP_AUTO_THREADS,
+    CAP_VARIABLE_FRAME_SIZE,
+    CAP_INTRA_ONLY,
+    CAP_LOSSLESS,
+};
+
+mod private_option;
+pub use self::private_option::{
+    PrivateOption,
+    PrivateOptionIter,
+};
@@ -0,0 +1,91 @@
+use std::fmt;
+use std::ptr;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use ffi::{
+    self,
+    AVClass,
+    AVOption,
+    AVOptionType,
+};
+use util::AsCStr;
+
+/// An option specific to a codec implementation (e.g. `preset` of libx264).
+pub struct PrivateOption {
+    ptr: *const AVOption
+}
+
+impl PrivateOption {
+    pub fn name(&self) -> &CStr {
+        unsafe { self.as_ref().name.as_cstr().unwrap() }
+    }
+
+    pub fn help(&self) -> Option<&CStr> {
+        unsafe { self.as_ref().help.as_cstr() }
+    }
+
+    pub fn kind(&self) -> AVOptionType {
+        self.as_ref().type_
+    }
+
+    /// The named constants group this option belongs to or takes its values from.
+    pub fn unit(&self) -> Option<&CStr> {
+        unsafe { self.as_ref().unit.as_cstr() }
+    }
+
+    /// Whether this is a named value of another option, not an option itself.
+    pub fn is_constant(&self) -> bool {
+        self.kind() == AVOptionType::AV_OPT_TYPE_CONST
+    }
+
+    pub fn as_ref(&self) -> &AVOption {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl fmt::Debug for PrivateOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PrivateOption")
+            .field("name", &self.name())
+            .field("kind", &self.kind())
+            .field("help", &self.help())
+            .field("unit", &self.unit())
+            .finish()
+    }
+}
+
+pub struct PrivateOptionIter {
+    class: *const AVClass,
+    prev: *const AVOption,
+}
+
+impl PrivateOptionIter {
+    pub unsafe fn from_class(class: *const AVClass) -> Self {
+        PrivateOptionIter {
+            class: class,
+            prev: ptr::null(),
+        }
+    }
+}
+
+impl Iterator for PrivateOptionIter {
+    type Item = PrivateOption;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            if self.class.is_null() {
+                return None;
+            }
+
+            // `av_opt_next` expects an object starting with an `AVClass` pointer
+            let obj = &self.class as *const *const AVClass as *const c_void;
+            let next = ffi::av_opt_next(obj, self.prev);
+            if next.is_null() {
+                None
+            } else {
+                self.prev = next;
+                Some(PrivateOption { ptr: next })
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::ffi::CStr;
+use std::os::raw::c_int;
 use ffi::{
     AVProfile,
     FF_PROFILE_UNKNOWN,
@@ -11,6 +12,13 @@ pub struct Profile {
 }
 
 impl Profile {
+    /// The `FF_PROFILE_*` value of this profile.
+    pub fn id(&self) -> c_int {
+        unsafe {
+            (*self.ptr).profile
+        }
+    }
+
     pub fn name(&self) -> &CStr {
         unsafe {
             (*self.ptr).name.as_cstr().unwrap()
@@ -21,6 +29,7 @@ impl Profile {
 impl fmt::Debug for Profile {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Profile")
+            .field("id", &self.id())
             .field("name", &self.name())
             .finish()
     }
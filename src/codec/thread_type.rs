@@ -0,0 +1,14 @@
+use std::os::raw::c_int;
+use ffi::{
+    FF_THREAD_FRAME,
+    FF_THREAD_SLICE,
+};
+
+bitflags! {
+    pub flags ThreadType: c_int {
+        /// Decode/encode more than one frame at once.
+        const THREAD_FRAME = FF_THREAD_FRAME as c_int,
+        /// Decode/encode more than one part of a single frame at once.
+        const THREAD_SLICE = FF_THREAD_SLICE as c_int,
+    }
+}
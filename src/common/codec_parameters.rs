@@ -1,6 +1,22 @@
 use std::marker::PhantomData;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::mem;
+use std::slice;
 use ffi::{self, AVCodecParameters, AVStream};
-use codec::MediaType;
+use codec::{CodecId, MediaType};
+use video::{
+    PixelFormat,
+    ColorRange,
+    ColorPrimaries,
+    ColorTransferCharacteristic,
+    ColorSpace,
+    ChromaLocation,
+    FieldOrder,
+};
+use audio::{ChannelLayout, SampleFormat};
+use util::{AsCStr, nonzero_rational};
+use common::Timebase;
 
 pub struct CodecParameters<'stream> {
     ptr: *mut AVCodecParameters,
@@ -19,11 +35,142 @@ impl<'stream> CodecParameters<'stream> {
         MediaType::from_raw(self.as_ref().codec_type)
     }
 
-    pub fn codec_id(&self) -> ffi::AVCodecID {
-        self.as_ref().codec_id
+    pub fn codec_id(&self) -> CodecId {
+        CodecId::from_raw(self.as_ref().codec_id)
     }
 
-    // TODO: remaining fields
+    /// Codec-specific data, e.g. the SPS and PPS of H.264 in MP4.
+    pub fn extradata(&self) -> &[u8] {
+        unsafe {
+            let params = self.as_ref();
+            if params.extradata.is_null() {
+                &[]
+            } else {
+                slice::from_raw_parts(params.extradata, params.extradata_size as usize)
+            }
+        }
+    }
+
+    /// The average bit rate, if known.
+    pub fn bit_rate(&self) -> Option<i64> {
+        match self.as_ref().bit_rate {
+            0 => None,
+            bit_rate => Some(bit_rate),
+        }
+    }
+
+    pub fn profile(&self) -> Option<c_int> {
+        match self.as_ref().profile {
+            ffi::FF_PROFILE_UNKNOWN => None,
+            profile => Some(profile),
+        }
+    }
+
+    /// The name of the profile, e.g. `High` for H.264.
+    pub fn profile_name(&self) -> Option<&'static CStr> {
+        self.profile().and_then(|profile| unsafe {
+            (ffi::avcodec_profile_name(self.as_ref().codec_id, profile) as *const c_char).as_cstr()
+        })
+    }
+
+    pub fn level(&self) -> Option<c_int> {
+        match self.as_ref().level {
+            ffi::FF_LEVEL_UNKNOWN => None,
+            level => Some(level),
+        }
+    }
+}
+
+/// Video parameters
+impl<'stream> CodecParameters<'stream> {
+    pub fn width(&self) -> usize {
+        self.as_ref().width as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.as_ref().height as usize
+    }
+
+    /// The pixel format of a video stream, if known.
+    pub fn pixel_format(&self) -> Option<PixelFormat> {
+        if self.media_type() != MediaType::Video || self.as_ref().format < 0 {
+            return None;
+        }
+
+        unsafe {
+            let pixel_format: ffi::AVPixelFormat = mem::transmute(self.as_ref().format);
+            Some(PixelFormat::from_raw(pixel_format))
+        }
+    }
+
+    /// The sample aspect ratio, if known.
+    pub fn sample_aspect_ratio(&self) -> Option<Timebase> {
+        nonzero_rational(self.as_ref().sample_aspect_ratio)
+    }
+
+    pub fn field_order(&self) -> FieldOrder {
+        FieldOrder::from_raw(self.as_ref().field_order)
+    }
+
+    pub fn color_range(&self) -> ColorRange {
+        ColorRange::from_raw(self.as_ref().color_range)
+    }
+
+    pub fn color_primaries(&self) -> ColorPrimaries {
+        ColorPrimaries::from_raw(self.as_ref().color_primaries)
+    }
+
+    pub fn color_trc(&self) -> ColorTransferCharacteristic {
+        ColorTransferCharacteristic::from_raw(self.as_ref().color_trc)
+    }
+
+    pub fn colorspace(&self) -> ColorSpace {
+        ColorSpace::from_raw(self.as_ref().color_space)
+    }
+
+    pub fn chroma_location(&self) -> ChromaLocation {
+        ChromaLocation::from_raw(self.as_ref().chroma_location)
+    }
+}
+
+/// Audio parameters
+impl<'stream> CodecParameters<'stream> {
+    /// The sample format of an audio stream, if known.
+    pub fn sample_format(&self) -> Option<SampleFormat> {
+        if self.media_type() != MediaType::Audio || self.as_ref().format < 0 {
+            return None;
+        }
+
+        unsafe {
+            let sample_format: ffi::AVSampleFormat = mem::transmute(self.as_ref().format);
+            Some(SampleFormat::from_raw(sample_format))
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.as_ref().sample_rate as u32
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.as_ref().channels as usize
+    }
+
+    /// The channel layout, if known.
+    /// Some formats only specify the number of channels.
+    pub fn channel_layout(&self) -> Option<ChannelLayout> {
+        match self.as_ref().channel_layout {
+            0 => None,
+            layout => Some(ChannelLayout::from_bits_truncate(layout)),
+        }
+    }
+
+    /// The number of samples per channel in a frame, if constant.
+    pub fn frame_size(&self) -> Option<usize> {
+        match self.as_ref().frame_size {
+            size if size <= 0 => None,
+            size => Some(size as usize),
+        }
+    }
 }
 
 impl<'stream> CodecParameters<'stream> {
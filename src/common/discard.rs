@@ -0,0 +1,19 @@
+raw_enum! {
+    /// Which packets of a stream to drop while demuxing or decoding.
+    pub enum Discard(AVDiscard) {
+        /// Drop nothing.
+        None = AVDISCARD_NONE,
+        /// Drop useless packets like 0 size packets in AVI.
+        Default = AVDISCARD_DEFAULT,
+        /// Drop all non-reference frames.
+        NonRef = AVDISCARD_NONREF,
+        /// Drop all bidirectional frames.
+        Bidirectional = AVDISCARD_BIDIR,
+        /// Drop all frames except intra frames.
+        NonIntra = AVDISCARD_NONINTRA,
+        /// Drop all frames except keyframes.
+        NonKey = AVDISCARD_NONKEY,
+        /// Drop everything.
+        All = AVDISCARD_ALL,
+    }
+}
@@ -0,0 +1,48 @@
+use std::os::raw::c_int;
+use ffi::{
+    AV_DISPOSITION_DEFAULT,
+    AV_DISPOSITION_DUB,
+    AV_DISPOSITION_ORIGINAL,
+    AV_DISPOSITION_COMMENT,
+    AV_DISPOSITION_LYRICS,
+    AV_DISPOSITION_KARAOKE,
+    AV_DISPOSITION_FORCED,
+    AV_DISPOSITION_HEARING_IMPAIRED,
+    AV_DISPOSITION_VISUAL_IMPAIRED,
+    AV_DISPOSITION_CLEAN_EFFECTS,
+    AV_DISPOSITION_ATTACHED_PIC,
+    AV_DISPOSITION_TIMED_THUMBNAILS,
+    AV_DISPOSITION_CAPTIONS,
+    AV_DISPOSITION_DESCRIPTIONS,
+    AV_DISPOSITION_METADATA,
+};
+
+bitflags! {
+    pub flags Disposition: c_int {
+        /// The stream should be chosen by default
+        const DISPOSITION_DEFAULT          = AV_DISPOSITION_DEFAULT          as c_int,
+        const DISPOSITION_DUB              = AV_DISPOSITION_DUB              as c_int,
+        const DISPOSITION_ORIGINAL         = AV_DISPOSITION_ORIGINAL         as c_int,
+        const DISPOSITION_COMMENT          = AV_DISPOSITION_COMMENT          as c_int,
+        const DISPOSITION_LYRICS           = AV_DISPOSITION_LYRICS           as c_int,
+        const DISPOSITION_KARAOKE          = AV_DISPOSITION_KARAOKE          as c_int,
+        /// The track should be used even if the user did not select subtitles
+        const DISPOSITION_FORCED           = AV_DISPOSITION_FORCED           as c_int,
+        /// The stream is intended for hearing impaired audiences
+        const DISPOSITION_HEARING_IMPAIRED = AV_DISPOSITION_HEARING_IMPAIRED as c_int,
+        /// The stream is intended for visually impaired audiences
+        const DISPOSITION_VISUAL_IMPAIRED  = AV_DISPOSITION_VISUAL_IMPAIRED  as c_int,
+        /// The stream contains music and sound effects without voice
+        const DISPOSITION_CLEAN_EFFECTS    = AV_DISPOSITION_CLEAN_EFFECTS    as c_int,
+        /// The stream is a single picture, e.g. cover art
+        const DISPOSITION_ATTACHED_PIC     = AV_DISPOSITION_ATTACHED_PIC     as c_int,
+        /// The stream contains thumbnails of the video
+        const DISPOSITION_TIMED_THUMBNAILS = AV_DISPOSITION_TIMED_THUMBNAILS as c_int,
+        /// The stream contains captions
+        const DISPOSITION_CAPTIONS         = AV_DISPOSITION_CAPTIONS         as c_int,
+        /// The stream contains audio descriptions
+        const DISPOSITION_DESCRIPTIONS     = AV_DISPOSITION_DESCRIPTIONS     as c_int,
+        /// The stream contains metadata that should not be presented
+        const DISPOSITION_METADATA         = AV_DISPOSITION_METADATA         as c_int,
+    }
+}
@@ -1,4 +1,4 @@
-use std::ptr;
+use std::os::raw::c_int;
 use ffi::{
     AVCodecContext,
     avcodec_open2,
@@ -11,6 +11,8 @@ use codec::{
     MediaType,
     Codec,
 };
+use common::Options;
+use common::options;
 use errors::*;
 
 pub unsafe fn init(codec_context: *mut AVCodecContext, format: OutputFormat) {
@@ -20,16 +22,24 @@ pub unsafe fn init(codec_context: *mut AVCodecContext, format: OutputFormat) {
     }
 }
 
-pub unsafe fn open(mut codec_context: *mut AVCodecContext, kind: &'static str) -> Result<()> {
+/// Open the encoder. Returns the options that were not consumed.
+pub unsafe fn open(mut codec_context: *mut AVCodecContext, kind: &'static str, options: &Options) -> Result<Vec<String>> {
     let codec = (*codec_context).codec;
-    let options = ptr::null_mut();
-    let res = avcodec_open2(codec_context, codec, options);
+    let mut options = match options.to_dict() {
+        Ok(options) => options,
+        Err(err) => {
+            avcodec_free_context(&mut codec_context);
+            return Err(err);
+        },
+    };
+    let res = avcodec_open2(codec_context, codec, &mut options);
+    let unused_options = options::take_unused(&mut options);
     if res < 0 {
         avcodec_free_context(&mut codec_context);
-        bail!(ErrorKind::OpenEncoder(kind));
+        bail!(ErrorKind::av(res, format!("Could not open {} encoder", kind)));
     }
 
-    Ok(())
+    Ok(unused_options)
 }
 
 pub fn require_is_encoder(codec: Codec) -> Result<()> {
@@ -47,3 +57,29 @@ pub fn require_codec_type(encoder_type: MediaType, codec: Codec) -> Result<()> {
         Ok(())
     }
 }
+
+pub fn invalid_setting<S: Into<String>>(setting: &'static str, reason: S) -> Result<()> {
+    Err(ErrorKind::InvalidEncoderSetting(setting, reason.into()).into())
+}
+
+/// Check that `profile` is one of the profiles known for `codec`.
+pub fn require_supported_profile(codec: Codec, profile: c_int) -> Result<()> {
+    let codec_name = codec.name().unwrap_or_default().to_string_lossy();
+
+    // Wrapped encoders (e.g. libx264) often only list their profiles in the descriptor
+    let mut profiles: Vec<_> = codec.profiles().map(|p| p.id()).collect();
+    if profiles.is_empty() {
+        if let Some(descriptor) = codec.descriptor() {
+            profiles.extend(descriptor.profiles().map(|p| p.id()));
+        }
+    }
+
+    if profiles.is_empty() {
+        return invalid_setting("profile", format!("{} does not support profiles", codec_name));
+    }
+    if !profiles.contains(&profile) {
+        return invalid_setting("profile", format!("{} does not support profile {}", codec_name, profile));
+    }
+
+    Ok(())
+}
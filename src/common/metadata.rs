@@ -0,0 +1,193 @@
+use std::marker::PhantomData;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::fmt;
+use ffi::{
+    self,
+    AVDictionary,
+    AVDictionaryEntry,
+    AV_DICT_MATCH_CASE,
+    AV_DICT_IGNORE_SUFFIX,
+};
+use util::AsCStr;
+use errors::*;
+
+/// A read-only view of a metadata dictionary
+/// (e.g. title, artist, language, encoder, creation_time, ...).
+///
+/// Lookups are case-insensitive unless stated otherwise.
+#[derive(Copy,Clone)]
+pub struct Metadata<'a> {
+    ptr: *const AVDictionary,
+    _phantom: PhantomData<&'a AVDictionary>,
+}
+
+impl<'a> Metadata<'a> {
+    pub unsafe fn from_ptr(ptr: *const AVDictionary) -> Metadata<'a> {
+        Metadata {
+            ptr: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the value for `key`, ignoring case.
+    pub fn get(&self, key: &str) -> Option<&'a CStr> {
+        self.get_with_flags(key, 0)
+    }
+
+    /// Get the value for `key`, matching case.
+    pub fn get_match_case(&self, key: &str) -> Option<&'a CStr> {
+        self.get_with_flags(key, AV_DICT_MATCH_CASE as c_int)
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { ffi::av_dict_count(self.ptr) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> MetadataIter<'a> {
+        MetadataIter {
+            ptr: self.ptr,
+            prev: ptr::null(),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn get_with_flags(&self, key: &str, flags: c_int) -> Option<&'a CStr> {
+        unsafe {
+            let key = match CString::new(key) {
+                Ok(key) => key,
+                Err(_) => return None,
+            };
+            let prev = ptr::null();
+            let entry = ffi::av_dict_get(self.ptr, key.as_ptr(), prev, flags);
+
+            if entry.is_null() {
+                None
+            } else {
+                ((*entry).value as *const c_char).as_cstr()
+            }
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVDictionary {
+        self.ptr
+    }
+}
+
+impl<'a> IntoIterator for Metadata<'a> {
+    type Item = (&'a CStr, &'a CStr);
+    type IntoIter = MetadataIter<'a>;
+
+    fn into_iter(self) -> MetadataIter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> fmt::Debug for Metadata<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A mutable view of a metadata dictionary.
+pub struct MetadataMut<'a> {
+    ptr: *mut *mut AVDictionary,
+    _phantom: PhantomData<&'a mut AVDictionary>,
+}
+
+impl<'a> MetadataMut<'a> {
+    pub unsafe fn from_ptr(ptr: *mut *mut AVDictionary) -> MetadataMut<'a> {
+        MetadataMut {
+            ptr: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn as_metadata(&self) -> Metadata {
+        unsafe { Metadata::from_ptr(*self.ptr) }
+    }
+
+    /// Get the value for `key`, ignoring case.
+    pub fn get(&self, key: &str) -> Option<&CStr> {
+        self.as_metadata().get(key)
+    }
+
+    /// Get the value for `key`, matching case.
+    pub fn get_match_case(&self, key: &str) -> Option<&CStr> {
+        self.as_metadata().get_match_case(key)
+    }
+
+    pub fn iter(&self) -> MetadataIter {
+        self.as_metadata().iter()
+    }
+
+    /// Set `key` to `value`, replacing an existing entry (ignoring case).
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        unsafe {
+            let key = CString::new(key).map_err(|_| "Metadata key contains nul byte")?;
+            let value = CString::new(value).map_err(|_| "Metadata value contains nul byte")?;
+            let flags = 0;
+
+            let res = ffi::av_dict_set(self.ptr, key.as_ptr(), value.as_ptr(), flags);
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Failed to set metadata {:?}", key)));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Remove the entry for `key` (ignoring case).
+    pub fn remove(&mut self, key: &str) -> Result<()> {
+        unsafe {
+            let key = CString::new(key).map_err(|_| "Metadata key contains nul byte")?;
+            let value = ptr::null();
+            let flags = 0;
+
+            let res = ffi::av_dict_set(self.ptr, key.as_ptr(), value, flags);
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Failed to remove metadata {:?}", key)));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+impl<'a> fmt::Debug for MetadataMut<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_metadata().fmt(f)
+    }
+}
+
+pub struct MetadataIter<'a> {
+    ptr: *const AVDictionary,
+    prev: *const AVDictionaryEntry,
+    _phantom: PhantomData<&'a AVDictionary>,
+}
+
+impl<'a> Iterator for MetadataIter<'a> {
+    type Item = (&'a CStr, &'a CStr);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            // An empty key with `AV_DICT_IGNORE_SUFFIX` matches every entry
+            let key = b"\0".as_ptr() as _;
+            let entry = ffi::av_dict_get(self.ptr, key, self.prev, AV_DICT_IGNORE_SUFFIX as c_int);
+
+            if entry.is_null() {
+                None
+            } else {
+                self.prev = entry;
+                let key = ((*entry).key as *const c_char).as_cstr().unwrap();
+                let value = ((*entry).value as *const c_char).as_cstr().unwrap();
+                Some((key, value))
+            }
+        }
+    }
+}
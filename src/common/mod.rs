@@ -3,8 +3,41 @@ pub mod stream;
 pub mod codec_parameters;
 mod packet;
 pub mod ts;
+pub mod metadata;
+pub mod options;
+pub mod side_data;
 mod timebase;
+mod disposition;
+mod discard;
 
-pub use self::packet::Packet;
-pub use self::ts::Ts;
-pub use self::timebase::Timebase;
+pub use self::packet::{
+    Packet,
+    PacketFlags,
+    PACKET_FLAG_KEY,
+    PACKET_FLAG_CORRUPT,
+    PACKET_FLAG_DISCARD,
+};
+pub use self::disposition::{
+    Disposition,
+    DISPOSITION_DEFAULT,
+    DISPOSITION_DUB,
+    DISPOSITION_ORIGINAL,
+    DISPOSITION_COMMENT,
+    DISPOSITION_LYRICS,
+    DISPOSITION_KARAOKE,
+    DISPOSITION_FORCED,
+    DISPOSITION_HEARING_IMPAIRED,
+    DISPOSITION_VISUAL_IMPAIRED,
+    DISPOSITION_CLEAN_EFFECTS,
+    DISPOSITION_ATTACHED_PIC,
+    DISPOSITION_TIMED_THUMBNAILS,
+    DISPOSITION_CAPTIONS,
+    DISPOSITION_DESCRIPTIONS,
+    DISPOSITION_METADATA,
+};
+pub use self::ts::{Ts, Rounding};
+pub use self::discard::Discard;
+pub use self::timebase::{Timebase, AV_TIME_BASE_Q};
+pub use self::metadata::{Metadata, MetadataMut};
+pub use self::options::Options;
+pub use self::side_data::{SideData, SideDataMut, SideDataType};
@@ -0,0 +1,181 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+use std::ptr;
+use std::fmt;
+use ffi::{
+    self,
+    AVDictionary,
+    AV_DICT_MATCH_CASE,
+};
+use common::Metadata;
+use common::metadata::MetadataIter;
+use errors::*;
+
+/// Options passed to libav when opening demuxers, muxers, encoders and decoders
+/// (e.g. `preset`, `movflags`, `probesize`).
+///
+/// Options not recognized by the opened component are reported back
+/// by the respective `unused_options` method.
+pub struct Options {
+    ptr: *mut AVDictionary,
+}
+
+unsafe impl Send for Options {}
+unsafe impl Sync for Options {}
+
+impl Options {
+    pub fn new() -> Self {
+        Options {
+            ptr: ptr::null_mut(),
+        }
+    }
+
+    /// Set option `key` to `value`, replacing a previous value.
+    /// Fails if `key` or `value` contain a nul byte.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        unsafe {
+            let key = CString::new(key).map_err(|_| "Option key contains nul byte")?;
+            let value = CString::new(value).map_err(|_| "Option value contains nul byte")?;
+            let flags = 0;
+
+            let res = ffi::av_dict_set(&mut self.ptr, key.as_ptr(), value.as_ptr(), flags);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not set option"));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Set option `key` to the integer `value`, replacing a previous value.
+    /// Fails if `key` contains a nul byte.
+    pub fn set_int(&mut self, key: &str, value: i64) -> Result<()> {
+        unsafe {
+            let key = CString::new(key).map_err(|_| "Option key contains nul byte")?;
+            let flags = 0;
+
+            let res = ffi::av_dict_set_int(&mut self.ptr, key.as_ptr(), value, flags);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not set option"));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Copy all options from `other`, replacing previous values.
+    pub fn extend(&mut self, other: &Options) -> Result<()> {
+        unsafe {
+            let flags = 0;
+            let res = ffi::av_dict_copy(&mut self.ptr, other.ptr, flags);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not copy options"));
+            }
+
+            Ok(())
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CStr> {
+        unsafe { Metadata::from_ptr(self.ptr).get_match_case(key) }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { ffi::av_dict_count(self.ptr) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> MetadataIter {
+        unsafe { Metadata::from_ptr(self.ptr).iter() }
+    }
+
+    /// Create a copy of the underlying dictionary
+    /// to be consumed by a libav function.
+    #[doc(hidden)]
+    pub unsafe fn to_dict(&self) -> Result<*mut AVDictionary> {
+        let mut dict = ptr::null_mut();
+        let flags = 0;
+
+        let res = ffi::av_dict_copy(&mut dict, self.ptr, flags);
+        if res < 0 {
+            ffi::av_dict_free(&mut dict);
+            bail!(ErrorKind::av(res, "Could not copy options"));
+        }
+
+        Ok(dict)
+    }
+
+    pub fn as_ptr(&self) -> *const AVDictionary {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVDictionary {
+        self.ptr
+    }
+}
+
+/// Collect the keys left in `dict` (i.e. not consumed by libav) and free it.
+#[doc(hidden)]
+pub unsafe fn take_unused(dict: &mut *mut AVDictionary) -> Vec<String> {
+    let unused = Metadata::from_ptr(*dict).iter()
+        .map(|(key, _)| key.to_string_lossy().into_owned())
+        .collect();
+
+    ffi::av_dict_free(dict);
+
+    unused
+}
+
+/// Like `take_unused`, but only reports keys of `dict` that are also left in every one
+/// of `consumers` (e.g. the per-stream dictionaries of `avformat_find_stream_info`).
+/// Frees `dict` and all `consumers`.
+#[doc(hidden)]
+pub unsafe fn take_unused_by_all(dict: &mut *mut AVDictionary, consumers: &mut [*mut AVDictionary]) -> Vec<String> {
+    let unused = Metadata::from_ptr(*dict).iter()
+        .filter(|&(key, _)| consumers.iter().all(|&consumer| {
+            let prev = ptr::null();
+            !ffi::av_dict_get(consumer, key.as_ptr(), prev, AV_DICT_MATCH_CASE as c_int).is_null()
+        }))
+        .map(|(key, _)| key.to_string_lossy().into_owned())
+        .collect();
+
+    ffi::av_dict_free(dict);
+    for consumer in consumers {
+        ffi::av_dict_free(consumer);
+    }
+
+    unused
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options::new()
+    }
+}
+
+impl Clone for Options {
+    fn clone(&self) -> Self {
+        unsafe {
+            Options {
+                ptr: self.to_dict().expect("Could not clone options"),
+            }
+        }
+    }
+}
+
+impl Drop for Options {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::av_dict_free(&mut self.ptr);
+        }
+    }
+}
+
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
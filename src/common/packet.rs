@@ -1,8 +1,31 @@
 use std::marker::PhantomData;
 use std::slice;
 use std::mem;
-use ffi::{self, AVPacket};
-use common::Timebase;
+use std::ptr;
+use std::iter;
+use std::os::raw::{c_int, c_void};
+use ffi::{
+    self,
+    AVPacket,
+    AVPacketSideDataType,
+    AV_PKT_FLAG_KEY,
+    AV_PKT_FLAG_CORRUPT,
+    AV_PKT_FLAG_DISCARD,
+    AV_INPUT_BUFFER_PADDING_SIZE,
+};
+use common::{Timebase, Ts};
+use errors::*;
+
+bitflags! {
+    pub flags PacketFlags: c_int {
+        /// The packet contains a keyframe
+        const PACKET_FLAG_KEY     = AV_PKT_FLAG_KEY     as c_int,
+        /// The packet content is corrupted
+        const PACKET_FLAG_CORRUPT = AV_PKT_FLAG_CORRUPT as c_int,
+        /// The packet is required for decoding but its frame should be discarded
+        const PACKET_FLAG_DISCARD = AV_PKT_FLAG_DISCARD as c_int,
+    }
+}
 
 /// A reference to a packet as returned
 /// e.g. by Demuxer::read_packet
@@ -25,10 +48,191 @@ impl<'buf> Packet<'buf> {
         }
     }
 
+    /// Create a refcounted packet by copying `data` into a padded buffer.
+    pub fn from_slice<TB: Into<Timebase>>(data: &[u8], time_base: TB) -> Result<Packet<'static>> {
+        unsafe {
+            if data.len() > c_int::max_value() as usize - AV_INPUT_BUFFER_PADDING_SIZE as usize {
+                bail!("Packet data too large");
+            }
+
+            let mut packet = ffi::av_packet_alloc();
+            if packet.is_null() {
+                bail!(ErrorKind::AllocFailed("packet"));
+            }
+
+            // Allocates a refcounted buffer including zeroed padding
+            let res = ffi::av_new_packet(packet, data.len() as c_int);
+            if res < 0 {
+                ffi::av_packet_free(&mut packet);
+                bail!(ErrorKind::av(res, "Could not allocate packet buffer"));
+            }
+
+            ptr::copy_nonoverlapping(data.as_ptr(), (*packet).data, data.len());
+
+            Ok(Packet::from_ptr(packet, time_base.into()))
+        }
+    }
+
+    /// Create a refcounted packet taking ownership of `data` without copying it.
+    /// The vector is reallocated if it has too little capacity for the required padding.
+    pub fn from_vec<TB: Into<Timebase>>(mut data: Vec<u8>, time_base: TB) -> Result<Packet<'static>> {
+        unsafe {
+            let size = data.len();
+            let padding = AV_INPUT_BUFFER_PADDING_SIZE as usize;
+
+            if size > c_int::max_value() as usize - padding {
+                bail!("Packet data too large");
+            }
+
+            // The padding after the data is required to be zeroed
+            data.extend(iter::repeat(0).take(padding));
+            data.truncate(size);
+
+            let data_ptr = data.as_mut_ptr();
+            let opaque = Box::into_raw(Box::new(data)) as *mut c_void;
+            let flags = 0;
+            let mut buf = ffi::av_buffer_create(data_ptr, (size + padding) as c_int, Some(free_vec_buffer), opaque, flags);
+            if buf.is_null() {
+                free_vec_buffer(opaque, data_ptr);
+                bail!(ErrorKind::AllocFailed("packet buffer"));
+            }
+
+            let packet = ffi::av_packet_alloc();
+            if packet.is_null() {
+                ffi::av_buffer_unref(&mut buf);
+                bail!(ErrorKind::AllocFailed("packet"));
+            }
+
+            (*packet).buf = buf;
+            (*packet).data = data_ptr;
+            (*packet).size = size as c_int;
+
+            Ok(Packet::from_ptr(packet, time_base.into()))
+        }
+    }
+
     pub fn stream_index(&self) -> usize {
         self.as_raw().stream_index as usize
     }
 
+    pub fn set_stream_index(&mut self, stream_index: usize) {
+        self.as_mut_raw().stream_index = stream_index as c_int;
+    }
+
+    /// Presentation timestamp in the packet's time base
+    pub fn pts(&self) -> Option<Ts> {
+        self.ts_from_raw(self.as_raw().pts)
+    }
+
+    /// Set the presentation timestamp, rescaling it to the packet's time base.
+    pub fn set_pts(&mut self, pts: Option<Ts>) {
+        self.as_mut_raw().pts = self.ts_into_raw(pts);
+    }
+
+    /// Decompression timestamp in the packet's time base
+    pub fn dts(&self) -> Option<Ts> {
+        self.ts_from_raw(self.as_raw().dts)
+    }
+
+    /// Set the decompression timestamp, rescaling it to the packet's time base.
+    pub fn set_dts(&mut self, dts: Option<Ts>) {
+        self.as_mut_raw().dts = self.ts_into_raw(dts);
+    }
+
+    /// Duration of the packet in the packet's time base, if known
+    pub fn duration(&self) -> Option<Ts> {
+        match self.as_raw().duration {
+            0 => None,
+            duration => Some(Ts::new(duration, self.time_base)),
+        }
+    }
+
+    /// Set the duration, rescaling it to the packet's time base.
+    pub fn set_duration(&mut self, duration: Option<Ts>) {
+        self.as_mut_raw().duration = match self.ts_into_raw(duration) {
+            ffi::NOPTS_VALUE => 0,
+            duration => duration,
+        };
+    }
+
+    /// Byte position of the packet in the stream, if known
+    pub fn pos(&self) -> Option<u64> {
+        match self.as_raw().pos {
+            pos if pos < 0 => None,
+            pos => Some(pos as u64),
+        }
+    }
+
+    pub fn set_pos(&mut self, pos: Option<u64>) {
+        self.as_mut_raw().pos = pos.map(|pos| pos as i64).unwrap_or(-1);
+    }
+
+    pub fn flags(&self) -> PacketFlags {
+        PacketFlags::from_bits_truncate(self.as_raw().flags)
+    }
+
+    pub fn set_flags(&mut self, flags: PacketFlags) {
+        self.as_mut_raw().flags = flags.bits();
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        self.flags().contains(PACKET_FLAG_KEY)
+    }
+
+    pub fn is_corrupt(&self) -> bool {
+        self.flags().contains(PACKET_FLAG_CORRUPT)
+    }
+
+    pub fn is_discard(&self) -> bool {
+        self.flags().contains(PACKET_FLAG_DISCARD)
+    }
+
+    /// Get the side data of the given type, if present.
+    pub fn side_data(&self, kind: AVPacketSideDataType) -> Option<&[u8]> {
+        unsafe {
+            let mut size = 0;
+            let data = ffi::av_packet_get_side_data(self.ptr, kind, &mut size);
+
+            if data.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(data, size as usize))
+            }
+        }
+    }
+
+    /// All side data entries of the packet.
+    pub fn side_data_entries(&self) -> Vec<(AVPacketSideDataType, &[u8])> {
+        unsafe {
+            let packet = self.as_raw();
+            if packet.side_data.is_null() {
+                return Vec::new();
+            }
+
+            slice::from_raw_parts(packet.side_data, packet.side_data_elems as usize).iter()
+                .map(|side_data| (side_data.type_, slice::from_raw_parts(side_data.data as *const u8, side_data.size as usize)))
+                .collect()
+        }
+    }
+
+    /// Attach a copy of `data` as side data of the given type.
+    pub fn add_side_data(&mut self, kind: AVPacketSideDataType, data: &[u8]) -> Result<()> {
+        unsafe {
+            if data.len() > c_int::max_value() as usize {
+                bail!("Packet side data too large");
+            }
+
+            let side_data = ffi::av_packet_new_side_data(self.ptr, kind, data.len() as c_int);
+            if side_data.is_null() {
+                bail!(ErrorKind::AllocFailed("packet side data"));
+            }
+
+            ptr::copy_nonoverlapping(data.as_ptr(), side_data, data.len());
+
+            Ok(())
+        }
+    }
+
     pub fn as_slice(&self) -> &[u8] {
         unsafe {
             let packet = self.as_raw();
@@ -61,6 +265,14 @@ impl<'buf> Packet<'buf> {
 
 // Private helpers
 impl<'buf> Packet<'buf> {
+    fn ts_from_raw(&self, index: i64) -> Option<Ts> {
+        Ts::from_raw(index, self.time_base)
+    }
+
+    fn ts_into_raw(&self, ts: Option<Ts>) -> i64 {
+        Ts::to_raw(ts, self.time_base)
+    }
+
     unsafe fn ref_packet(ptr: *const AVPacket) -> *mut AVPacket {
         let packet = ffi::av_packet_alloc();
         if packet.is_null() {
@@ -108,6 +320,9 @@ impl<'buf> Packet<'buf> {
     pub fn as_raw(&self) -> &AVPacket {
         unsafe { &*self.ptr }
     }
+    pub fn as_mut_raw(&mut self) -> &mut AVPacket {
+        unsafe { &mut *self.ptr }
+    }
     pub fn as_ptr(&self) -> *const AVPacket {
         self.ptr
     }
@@ -123,3 +338,7 @@ impl<'buf> Drop for Packet<'buf> {
         }
     }
 }
+
+unsafe extern "C" fn free_vec_buffer(opaque: *mut c_void, _data: *mut u8) {
+    Box::from_raw(opaque as *mut Vec<u8>);
+}
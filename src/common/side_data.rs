@@ -0,0 +1,694 @@
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::fmt;
+use ffi::{
+    self,
+    AVFrame,
+    AVFrameSideData,
+    AVRational,
+    AVMotionVector,
+};
+use common::Metadata;
+use errors::*;
+
+raw_enum! {
+    /// The kind of data attached to a frame.
+    pub enum SideDataType(AVFrameSideDataType) {
+        PanScan = AV_FRAME_DATA_PANSCAN,
+        /// ATSC A53 Part 4 closed captions.
+        A53ClosedCaptions = AV_FRAME_DATA_A53_CC,
+        Stereo3D = AV_FRAME_DATA_STEREO3D,
+        MatrixEncoding = AV_FRAME_DATA_MATRIXENCODING,
+        DownmixInfo = AV_FRAME_DATA_DOWNMIX_INFO,
+        ReplayGain = AV_FRAME_DATA_REPLAYGAIN,
+        DisplayMatrix = AV_FRAME_DATA_DISPLAYMATRIX,
+        ActiveFormatDescription = AV_FRAME_DATA_AFD,
+        MotionVectors = AV_FRAME_DATA_MOTION_VECTORS,
+        SkipSamples = AV_FRAME_DATA_SKIP_SAMPLES,
+        AudioServiceType = AV_FRAME_DATA_AUDIO_SERVICE_TYPE,
+        MasteringDisplayMetadata = AV_FRAME_DATA_MASTERING_DISPLAY_METADATA,
+        GopTimecode = AV_FRAME_DATA_GOP_TIMECODE,
+        ContentLightLevel = AV_FRAME_DATA_CONTENT_LIGHT_LEVEL,
+    }
+}
+
+raw_enum! {
+    /// How the views of a stereoscopic frame are packed.
+    pub enum Stereo3DType(AVStereo3DType) {
+        /// A regular 2D frame.
+        TwoD = AV_STEREO3D_2D,
+        SideBySide = AV_STEREO3D_SIDEBYSIDE,
+        TopBottom = AV_STEREO3D_TOPBOTTOM,
+        /// The views alternate from frame to frame.
+        FrameSequence = AV_STEREO3D_FRAMESEQUENCE,
+        Checkerboard = AV_STEREO3D_CHECKERBOARD,
+        SideBySideQuincunx = AV_STEREO3D_SIDEBYSIDE_QUINCUNX,
+        Lines = AV_STEREO3D_LINES,
+        Columns = AV_STEREO3D_COLUMNS,
+    }
+}
+
+/// A 3x3 transformation matrix describing how a video frame
+/// should be transformed for display, in 16.16 fixed point
+/// (except for the last column, which is 2.30).
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct DisplayMatrix(pub [i32; 9]);
+
+impl DisplayMatrix {
+    /// A matrix rotating by `angle` degrees counter-clockwise.
+    pub fn from_rotation(angle: f64) -> Self {
+        unsafe {
+            let mut matrix = [0; 9];
+            ffi::av_display_rotation_set(matrix.as_mut_ptr(), angle);
+            DisplayMatrix(matrix)
+        }
+    }
+
+    /// The counter-clockwise rotation in degrees, in the range [-180, 180].
+    /// Returns `None` if the matrix is invalid.
+    ///
+    /// To orient a frame for display, rotate it counter-clockwise by this angle
+    /// (clockwise by `-angle`).
+    pub fn rotation(&self) -> Option<f64> {
+        unsafe {
+            let angle = ffi::av_display_rotation_get(self.0.as_ptr());
+            if angle.is_nan() {
+                None
+            } else {
+                Some(angle)
+            }
+        }
+    }
+
+    pub fn flip(&mut self, horizontal: bool, vertical: bool) {
+        unsafe {
+            ffi::av_display_matrix_flip(self.0.as_mut_ptr(), horizontal as c_int, vertical as c_int);
+        }
+    }
+}
+
+/// The color volume of the display used to master the content (SMPTE 2086).
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct MasteringDisplay {
+    pub primaries: Option<DisplayPrimaries>,
+    /// Minimum and maximum luminance in cd/m².
+    pub luminance: Option<(f64, f64)>,
+}
+
+/// CIE 1931 xy chromaticity coordinates.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct DisplayPrimaries {
+    pub red: (f64, f64),
+    pub green: (f64, f64),
+    pub blue: (f64, f64),
+    pub white_point: (f64, f64),
+}
+
+// Chromaticities and luminances are stored with the precision used by HEVC SEI messages
+const CHROMATICITY_DEN: c_int = 50000;
+const LUMINANCE_DEN: c_int = 10000;
+
+impl MasteringDisplay {
+    fn from_raw(raw: &ffi::AVMasteringDisplayMetadata) -> Self {
+        let xy = |xy: &[AVRational; 2]| (rational_to_f64(xy[0]), rational_to_f64(xy[1]));
+
+        MasteringDisplay {
+            primaries: if raw.has_primaries != 0 {
+                Some(DisplayPrimaries {
+                    red: xy(&raw.display_primaries[0]),
+                    green: xy(&raw.display_primaries[1]),
+                    blue: xy(&raw.display_primaries[2]),
+                    white_point: xy(&raw.white_point),
+                })
+            } else {
+                None
+            },
+            luminance: if raw.has_luminance != 0 {
+                Some((rational_to_f64(raw.min_luminance), rational_to_f64(raw.max_luminance)))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn write_raw(&self, raw: &mut ffi::AVMasteringDisplayMetadata) {
+        let xy = |(x, y): (f64, f64)| [f64_to_rational(x, CHROMATICITY_DEN), f64_to_rational(y, CHROMATICITY_DEN)];
+
+        if let Some(primaries) = self.primaries {
+            raw.display_primaries = [xy(primaries.red), xy(primaries.green), xy(primaries.blue)];
+            raw.white_point = xy(primaries.white_point);
+        }
+        raw.has_primaries = self.primaries.is_some() as c_int;
+
+        if let Some((min, max)) = self.luminance {
+            raw.min_luminance = f64_to_rational(min, LUMINANCE_DEN);
+            raw.max_luminance = f64_to_rational(max, LUMINANCE_DEN);
+        }
+        raw.has_luminance = self.luminance.is_some() as c_int;
+    }
+}
+
+/// The light level of the content (CTA-861.3).
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct ContentLightLevel {
+    /// Maximum content light level in cd/m².
+    pub max_cll: u32,
+    /// Maximum frame-average light level in cd/m².
+    pub max_fall: u32,
+}
+
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct Stereo3D {
+    pub kind: Stereo3DType,
+    /// Whether the views are swapped, i.e. the right view comes first.
+    pub inverted: bool,
+}
+
+/// ReplayGain values. Gains are in dB, peaks are relative to full scale.
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct ReplayGain {
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+// Gains are stored in microbels, peaks in units of 1/100000 of full scale
+const REPLAYGAIN_SCALE: f32 = 100000.0;
+
+impl ReplayGain {
+    fn from_raw(raw: &ffi::AVReplayGain) -> Self {
+        let gain = |gain: i32| if gain == i32::min_value() { None } else { Some(gain as f32 / REPLAYGAIN_SCALE) };
+        let peak = |peak: u32| if peak == 0 { None } else { Some(peak as f32 / REPLAYGAIN_SCALE) };
+
+        ReplayGain {
+            track_gain: gain(raw.track_gain),
+            track_peak: peak(raw.track_peak),
+            album_gain: gain(raw.album_gain),
+            album_peak: peak(raw.album_peak),
+        }
+    }
+
+    fn as_raw(&self) -> ffi::AVReplayGain {
+        let gain = |gain: Option<f32>| gain.map(|gain| (gain * REPLAYGAIN_SCALE).round() as i32).unwrap_or(i32::min_value());
+        let peak = |peak: Option<f32>| peak.map(|peak| (peak * REPLAYGAIN_SCALE).round() as u32).unwrap_or(0);
+
+        ffi::AVReplayGain {
+            track_gain: gain(self.track_gain),
+            track_peak: peak(self.track_peak),
+            album_gain: gain(self.album_gain),
+            album_peak: peak(self.album_peak),
+        }
+    }
+}
+
+/// The motion of a block, relative to a past or future frame.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub struct MotionVector {
+    /// Where the block is predicted from.
+    /// Negative for a past frame and positive for a future frame.
+    pub source: i32,
+    /// Block width and height.
+    pub size: (u8, u8),
+    /// Absolute source position of the block center.
+    pub src: (i16, i16),
+    /// Absolute destination position of the block center.
+    pub dst: (i16, i16),
+    /// Extra flags, currently unused.
+    pub flags: u64,
+    /// The motion in units of `1 / motion_scale` pixels,
+    /// i.e. `src = dst + motion / motion_scale`.
+    pub motion: (i32, i32),
+    pub motion_scale: u16,
+}
+
+impl MotionVector {
+    fn from_raw(raw: &AVMotionVector) -> Self {
+        MotionVector {
+            source: raw.source,
+            size: (raw.w, raw.h),
+            src: (raw.src_x, raw.src_y),
+            dst: (raw.dst_x, raw.dst_y),
+            flags: raw.flags,
+            motion: (raw.motion_x, raw.motion_y),
+            motion_scale: raw.motion_scale,
+        }
+    }
+
+    fn as_raw(&self) -> AVMotionVector {
+        AVMotionVector {
+            source: self.source,
+            w: self.size.0,
+            h: self.size.1,
+            src_x: self.src.0,
+            src_y: self.src.1,
+            dst_x: self.dst.0,
+            dst_y: self.dst.1,
+            flags: self.flags,
+            motion_x: self.motion.0,
+            motion_y: self.motion.1,
+            motion_scale: self.motion_scale,
+        }
+    }
+}
+
+fn rational_to_f64(rational: AVRational) -> f64 {
+    if rational.den == 0 {
+        0.0
+    } else {
+        rational.num as f64 / rational.den as f64
+    }
+}
+
+fn f64_to_rational(value: f64, den: c_int) -> AVRational {
+    AVRational {
+        num: (value * den as f64).round() as c_int,
+        den: den,
+    }
+}
+
+/// A read-only view of the side data attached to a frame.
+#[derive(Copy,Clone)]
+pub struct SideData<'a> {
+    ptr: *const AVFrame,
+    _phantom: PhantomData<&'a AVFrame>,
+}
+
+impl<'a> SideData<'a> {
+    pub unsafe fn from_ptr(ptr: *const AVFrame) -> SideData<'a> {
+        SideData {
+            ptr: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { (*self.ptr).nb_side_data as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> SideDataIter<'a> {
+        SideDataIter {
+            ptr: self.ptr,
+            index: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the first entry of the given kind.
+    pub fn get(&self, kind: SideDataType) -> Option<SideDataEntry<'a>> {
+        unsafe {
+            let entry = ffi::av_frame_get_side_data(self.ptr, kind.as_raw());
+            if entry.is_null() {
+                None
+            } else {
+                Some(SideDataEntry::from_ptr(entry))
+            }
+        }
+    }
+
+    pub fn display_matrix(&self) -> Option<DisplayMatrix> {
+        self.get_as::<[i32; 9]>(SideDataType::DisplayMatrix).map(|matrix| DisplayMatrix(*matrix))
+    }
+
+    /// The counter-clockwise rotation in degrees the frame should be displayed with.
+    /// See `DisplayMatrix::rotation`.
+    pub fn rotation(&self) -> Option<f64> {
+        self.display_matrix().and_then(|matrix| matrix.rotation())
+    }
+
+    pub fn mastering_display(&self) -> Option<MasteringDisplay> {
+        self.get_as(SideDataType::MasteringDisplayMetadata).map(MasteringDisplay::from_raw)
+    }
+
+    pub fn content_light_level(&self) -> Option<ContentLightLevel> {
+        self.get_as::<ffi::AVContentLightMetadata>(SideDataType::ContentLightLevel).map(|raw| ContentLightLevel {
+            max_cll: raw.MaxCLL,
+            max_fall: raw.MaxFALL,
+        })
+    }
+
+    /// The raw A53 closed caption data, a sequence of `cc_data` triplets.
+    pub fn closed_captions(&self) -> Option<&'a [u8]> {
+        self.get(SideDataType::A53ClosedCaptions).map(|entry| entry.data())
+    }
+
+    pub fn stereo3d(&self) -> Option<Stereo3D> {
+        self.get_as::<ffi::AVStereo3D>(SideDataType::Stereo3D).map(|raw| Stereo3D {
+            kind: Stereo3DType::from_raw(raw.type_),
+            inverted: raw.flags & ffi::AV_STEREO3D_FLAG_INVERT as c_int != 0,
+        })
+    }
+
+    pub fn replay_gain(&self) -> Option<ReplayGain> {
+        self.get_as(SideDataType::ReplayGain).map(ReplayGain::from_raw)
+    }
+
+    /// The motion vectors exported by the decoder.
+    /// Decoders only export them if the `flags2` codec option contains `+export_mvs`.
+    pub fn motion_vectors(&self) -> Option<Vec<MotionVector>> {
+        self.get(SideDataType::MotionVectors).map(|entry| unsafe {
+            let data = entry.data();
+            let len = data.len() / mem::size_of::<AVMotionVector>();
+            slice::from_raw_parts(data.as_ptr() as *const AVMotionVector, len)
+                .iter().map(MotionVector::from_raw).collect()
+        })
+    }
+
+    /// Interpret the first entry of the given kind as a `T`.
+    /// Returns `None` if there is no such entry or it is too small.
+    fn get_as<T>(&self, kind: SideDataType) -> Option<&'a T> {
+        self.get(kind).and_then(|entry| {
+            let data = entry.data();
+            if data.len() < mem::size_of::<T>() {
+                None
+            } else {
+                unsafe { Some(&*(data.as_ptr() as *const T)) }
+            }
+        })
+    }
+
+    pub fn as_ptr(&self) -> *const AVFrame {
+        self.ptr
+    }
+}
+
+impl<'a> IntoIterator for SideData<'a> {
+    type Item = SideDataEntry<'a>;
+    type IntoIter = SideDataIter<'a>;
+
+    fn into_iter(self) -> SideDataIter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> fmt::Debug for SideData<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A mutable view of the side data attached to a frame.
+///
+/// Setters replace all existing entries of the same kind.
+/// The existing entries may be shared with other frames,
+/// so they are never modified in place.
+pub struct SideDataMut<'a> {
+    ptr: *mut AVFrame,
+    _phantom: PhantomData<&'a mut AVFrame>,
+}
+
+impl<'a> SideDataMut<'a> {
+    pub unsafe fn from_ptr(ptr: *mut AVFrame) -> SideDataMut<'a> {
+        SideDataMut {
+            ptr: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn as_side_data(&self) -> SideData {
+        unsafe { SideData::from_ptr(self.ptr) }
+    }
+
+    pub fn iter(&self) -> SideDataIter {
+        self.as_side_data().iter()
+    }
+
+    /// Replace the entries of the given kind with `data`.
+    pub fn set(&mut self, kind: SideDataType, data: &[u8]) -> Result<()> {
+        let buf = self.new_entry(kind, data.len())?;
+        buf.copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Remove all entries of the given kind.
+    pub fn remove(&mut self, kind: SideDataType) {
+        unsafe {
+            ffi::av_frame_remove_side_data(self.ptr, kind.as_raw());
+        }
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        unsafe {
+            while (*self.ptr).nb_side_data > 0 {
+                let kind = (**(*self.ptr).side_data).type_;
+                ffi::av_frame_remove_side_data(self.ptr, kind);
+            }
+        }
+    }
+
+    /// Replace all entries with copies of the entries in `side_data`.
+    pub fn copy_from(&mut self, side_data: SideData) -> Result<()> {
+        self.clear();
+
+        for entry in side_data {
+            unsafe {
+                let data = entry.data();
+                let new_entry = self.alloc_entry(entry.kind(), data.len())?;
+                slice::from_raw_parts_mut((*new_entry).data, data.len()).copy_from_slice(data);
+
+                let res = ffi::av_dict_copy(&mut (*new_entry).metadata, entry.as_ref().metadata, 0);
+                if res < 0 {
+                    bail!(ErrorKind::av(res, "Could not copy side data metadata"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_display_matrix(&mut self, matrix: DisplayMatrix) -> Result<()> {
+        self.set_as(SideDataType::DisplayMatrix, matrix.0)
+    }
+
+    /// Set the counter-clockwise rotation in degrees the frame should be displayed with.
+    pub fn set_rotation(&mut self, angle: f64) -> Result<()> {
+        self.set_display_matrix(DisplayMatrix::from_rotation(angle))
+    }
+
+    pub fn set_mastering_display(&mut self, mastering_display: MasteringDisplay) -> Result<()> {
+        unsafe {
+            self.remove(SideDataType::MasteringDisplayMetadata);
+
+            let raw = ffi::av_mastering_display_metadata_create_side_data(self.ptr);
+            if raw.is_null() {
+                bail!(ErrorKind::AllocFailed("mastering display side data"));
+            }
+
+            mastering_display.write_raw(&mut *raw);
+
+            Ok(())
+        }
+    }
+
+    pub fn set_content_light_level(&mut self, content_light_level: ContentLightLevel) -> Result<()> {
+        unsafe {
+            self.remove(SideDataType::ContentLightLevel);
+
+            let raw = ffi::av_content_light_metadata_create_side_data(self.ptr);
+            if raw.is_null() {
+                bail!(ErrorKind::AllocFailed("content light level side data"));
+            }
+
+            (*raw).MaxCLL = content_light_level.max_cll;
+            (*raw).MaxFALL = content_light_level.max_fall;
+
+            Ok(())
+        }
+    }
+
+    /// Set the raw A53 closed caption data, a sequence of `cc_data` triplets.
+    /// Encoders like libx264 only embed them if their `a53cc` option is set.
+    pub fn set_closed_captions(&mut self, data: &[u8]) -> Result<()> {
+        self.set(SideDataType::A53ClosedCaptions, data)
+    }
+
+    pub fn set_stereo3d(&mut self, stereo3d: Stereo3D) -> Result<()> {
+        unsafe {
+            self.remove(SideDataType::Stereo3D);
+
+            let raw = ffi::av_stereo3d_create_side_data(self.ptr);
+            if raw.is_null() {
+                bail!(ErrorKind::AllocFailed("stereo3d side data"));
+            }
+
+            (*raw).type_ = stereo3d.kind.as_raw();
+            (*raw).flags = if stereo3d.inverted { ffi::AV_STEREO3D_FLAG_INVERT as c_int } else { 0 };
+
+            Ok(())
+        }
+    }
+
+    pub fn set_replay_gain(&mut self, replay_gain: ReplayGain) -> Result<()> {
+        self.set_as(SideDataType::ReplayGain, replay_gain.as_raw())
+    }
+
+    pub fn set_motion_vectors(&mut self, motion_vectors: &[MotionVector]) -> Result<()> {
+        unsafe {
+            let raw: Vec<AVMotionVector> = motion_vectors.iter().map(MotionVector::as_raw).collect();
+            let len = raw.len() * mem::size_of::<AVMotionVector>();
+            let data = slice::from_raw_parts(raw.as_ptr() as *const u8, len);
+            self.set(SideDataType::MotionVectors, data)
+        }
+    }
+
+    /// Replace the entries of the given kind with the bytes of `value`.
+    fn set_as<T: Copy>(&mut self, kind: SideDataType, value: T) -> Result<()> {
+        unsafe {
+            let entry = self.new_entry(kind, mem::size_of::<T>())?;
+            ptr::write(entry.as_mut_ptr() as *mut T, value);
+            Ok(())
+        }
+    }
+
+    /// Replace the entries of the given kind with a new entry of `size` bytes.
+    fn new_entry(&mut self, kind: SideDataType, size: usize) -> Result<&mut [u8]> {
+        unsafe {
+            self.remove(kind);
+            let entry = self.alloc_entry(kind, size)?;
+            Ok(slice::from_raw_parts_mut((*entry).data, size))
+        }
+    }
+
+    unsafe fn alloc_entry(&mut self, kind: SideDataType, size: usize) -> Result<*mut AVFrameSideData> {
+        if size > c_int::max_value() as usize {
+            bail!("Side data exceeds c_int::max_value() bytes");
+        }
+
+        let entry = ffi::av_frame_new_side_data(self.ptr, kind.as_raw(), size as c_int);
+        if entry.is_null() {
+            bail!(ErrorKind::AllocFailed("frame side data"));
+        }
+
+        Ok(entry)
+    }
+}
+
+impl<'a> fmt::Debug for SideDataMut<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_side_data().fmt(f)
+    }
+}
+
+/// A single piece of side data.
+#[derive(Copy,Clone)]
+pub struct SideDataEntry<'a> {
+    ptr: *const AVFrameSideData,
+    _phantom: PhantomData<&'a AVFrameSideData>,
+}
+
+impl<'a> SideDataEntry<'a> {
+    pub unsafe fn from_ptr(ptr: *const AVFrameSideData) -> SideDataEntry<'a> {
+        SideDataEntry {
+            ptr: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn kind(&self) -> SideDataType {
+        SideDataType::from_raw(self.as_ref().type_)
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        unsafe {
+            let entry = &*self.ptr;
+            if entry.data.is_null() {
+                &[]
+            } else {
+                slice::from_raw_parts(entry.data, entry.size as usize)
+            }
+        }
+    }
+
+    pub fn metadata(&self) -> Metadata<'a> {
+        unsafe { Metadata::from_ptr(self.as_ref().metadata) }
+    }
+
+    pub fn as_ref(&self) -> &'a AVFrameSideData {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl<'a> fmt::Debug for SideDataEntry<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SideDataEntry")
+            .field("kind", &self.kind())
+            .field("size", &self.data().len())
+            .finish()
+    }
+}
+
+pub struct SideDataIter<'a> {
+    ptr: *const AVFrame,
+    index: usize,
+    _phantom: PhantomData<&'a AVFrame>,
+}
+
+impl<'a> Iterator for SideDataIter<'a> {
+    type Item = SideDataEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            let frame = &*self.ptr;
+            if self.index >= frame.nb_side_data as usize {
+                return None;
+            }
+
+            let entry = *frame.side_data.offset(self.index as isize);
+            self.index += 1;
+
+            Some(SideDataEntry::from_ptr(entry))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replay_gain_round_trip() {
+        let replay_gain = ReplayGain {
+            track_gain: Some(-6.5),
+            track_peak: Some(0.98765),
+            album_gain: None,
+            album_peak: None,
+        };
+        let raw = replay_gain.as_raw();
+
+        assert_eq!(raw.track_gain, -650000);
+        assert_eq!(raw.track_peak, 98765);
+        assert_eq!(raw.album_gain, i32::min_value());
+        assert_eq!(raw.album_peak, 0);
+        assert_eq!(ReplayGain::from_raw(&raw), replay_gain);
+    }
+
+    #[test]
+    fn display_matrix_rotation() {
+        let rotation = DisplayMatrix::from_rotation(90.0).rotation().unwrap();
+
+        assert!((rotation - 90.0).abs() < 1e-3, "rotation is {}", rotation);
+    }
+
+    #[test]
+    fn motion_vector_round_trip() {
+        let motion_vector = MotionVector {
+            source: -1,
+            size: (16, 8),
+            src: (10, -4),
+            dst: (8, 0),
+            flags: 0,
+            motion: (8, -16),
+            motion_scale: 4,
+        };
+
+        assert_eq!(MotionVector::from_raw(&motion_vector.as_raw()), motion_vector);
+    }
+}
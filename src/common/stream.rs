@@ -1,8 +1,12 @@
 use std::marker::PhantomData;
+use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::ptr;
 use ffi::{self, AVStream, AVFormatContext};
 use std::slice;
 use common::codec_parameters::CodecParameters;
-use common::Timebase;
+use common::{Timebase, Ts, Metadata, Disposition, DISPOSITION_ATTACHED_PIC, Discard};
+use util::nonzero_rational;
 
 pub struct Stream<'fmt_ctx> {
     ptr: *mut AVStream,
@@ -21,19 +25,23 @@ impl<'fmt_ctx> Stream<'fmt_ctx> {
         self.as_ref().index as usize
     }
 
-    // TODO: investigate what the "Format-specific stream ID" is
-    // pub fn id(&self) -> usize {
-    //     self.as_ref().id as usize
-    // }
+    /// The format-specific stream ID, e.g. the PID in MPEG-TS.
+    pub fn id(&self) -> c_int {
+        self.as_ref().id
+    }
 
     pub fn time_base(&self) -> Timebase {
         self.as_ref().time_base.into()
     }
 
-    // TODO: start_time
+    /// The presentation timestamp of the first frame, if known.
+    pub fn start_time(&self) -> Option<Ts> {
+        Ts::from_raw(self.as_ref().start_time, self.time_base())
+    }
 
-    pub fn duration(&self) -> usize {
-        self.as_ref().duration as usize
+    /// The duration in the time base of the stream, if known.
+    pub fn duration(&self) -> Option<Ts> {
+        Ts::from_raw(self.as_ref().duration, self.time_base())
     }
 
     /// Returns the number of frames if known
@@ -47,9 +55,68 @@ impl<'fmt_ctx> Stream<'fmt_ctx> {
         }
     }
 
-    // TODO: disposition
+    /// The average frame rate, if known.
+    pub fn avg_frame_rate(&self) -> Option<Timebase> {
+        nonzero_rational(self.as_ref().avg_frame_rate)
+    }
+
+    /// The lowest frame rate with which all timestamps can be represented, if known.
+    pub fn real_frame_rate(&self) -> Option<Timebase> {
+        nonzero_rational(self.as_ref().r_frame_rate)
+    }
+
+    /// The frame rate guessed from the container and codec information, if known.
+    /// This is usually the most accurate one.
+    pub fn guess_frame_rate(&self) -> Option<Timebase> {
+        unsafe {
+            // The format context and frame are not used for guessing
+            let format_context = ptr::null_mut();
+            let frame = ptr::null_mut();
+            nonzero_rational(ffi::av_guess_frame_rate(format_context, self.ptr, frame))
+        }
+    }
+
+    /// The sample aspect ratio stored by the container, if known.
+    pub fn sample_aspect_ratio(&self) -> Option<Timebase> {
+        nonzero_rational(self.as_ref().sample_aspect_ratio)
+    }
+
+    /// Which packets of the stream are dropped.
+    pub fn discard(&self) -> Discard {
+        Discard::from_raw(self.as_ref().discard)
+    }
+
+    pub fn disposition(&self) -> Disposition {
+        Disposition::from_bits_truncate(self.as_ref().disposition)
+    }
+
+    /// The ISO 639-2 language code from the metadata, e.g. `eng`.
+    pub fn language(&self) -> Option<&CStr> {
+        self.metadata().get("language")
+    }
 
-    // TODO: remaining fields
+    /// The encoded picture (e.g. cover art) of an attached picture stream.
+    /// The codec parameters describe its format.
+    pub fn attached_picture(&self) -> Option<&[u8]> {
+        if !self.disposition().contains(DISPOSITION_ATTACHED_PIC) {
+            return None;
+        }
+
+        unsafe {
+            let packet = &self.as_ref().attached_pic;
+            if packet.data.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(packet.data, packet.size as usize))
+            }
+        }
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        unsafe {
+            Metadata::from_ptr(self.as_ref().metadata)
+        }
+    }
 
     pub fn codec_parameters(&self) -> CodecParameters {
         unsafe {
@@ -1,6 +1,15 @@
 use std::os::raw::c_int;
-use ffi::AVRational;
+use ffi::{self, AVRational, AV_TIME_BASE};
 
+/// The internal time base of libav (microseconds),
+/// used e.g. for container durations.
+pub const AV_TIME_BASE_Q: Timebase = Timebase(AVRational {
+    num: 1,
+    den: AV_TIME_BASE as c_int,
+});
+
+/// A rational number, used for time bases as well as for
+/// frame rates and sample aspect ratios.
 #[derive(Copy,Clone,Debug)]
 pub struct Timebase(AVRational);
 
@@ -27,6 +36,22 @@ impl Timebase {
     pub fn as_f64(&self) -> f64 {
         self.num() as f64 / self.den() as f64
     }
+
+    /// The closest rational to `value` whose numerator and denominator
+    /// don't exceed `max`, e.g. `Timebase::from_f64(29.97, 100_000)` is `2997/100`.
+    pub fn from_f64(value: f64, max: c_int) -> Self {
+        unsafe { ffi::av_d2q(value, max).into() }
+    }
+
+    /// Reduce the fraction, e.g. `2/50` to `1/25`.
+    pub fn reduce(&self) -> Self {
+        unsafe {
+            let mut num = 0;
+            let mut den = 0;
+            ffi::av_reduce(&mut num, &mut den, self.num() as i64, self.den() as i64, c_int::max_value() as i64);
+            Timebase::new(num, den)
+        }
+    }
 }
 
 impl From<AVRational> for Timebase {
@@ -1,10 +1,43 @@
 use std::cmp;
 use std::ops;
-use std::time::Instant;
-use ffi::av_compare_ts;
+use std::time::{Duration, Instant};
+use ffi::{self, av_compare_ts, AVRounding};
 use common::Timebase;
 
-#[derive(Copy,Clone)]
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// How to round when rescaling timestamps.
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum Rounding {
+    /// Round toward zero.
+    Zero,
+    /// Round away from zero.
+    Inf,
+    /// Round toward -infinity.
+    Down,
+    /// Round toward +infinity.
+    Up,
+    /// Round to the nearest value and halfway cases away from zero.
+    NearInf,
+}
+
+impl Rounding {
+    pub fn as_raw(self) -> AVRounding {
+        match self {
+            Rounding::Zero    => AVRounding::AV_ROUND_ZERO,
+            Rounding::Inf     => AVRounding::AV_ROUND_INF,
+            Rounding::Down    => AVRounding::AV_ROUND_DOWN,
+            Rounding::Up      => AVRounding::AV_ROUND_UP,
+            Rounding::NearInf => AVRounding::AV_ROUND_NEAR_INF,
+        }
+    }
+}
+
+/// A timestamp, i.e. an index counting in units of a time base.
+///
+/// Comparison works across time bases.
+/// Arithmetic with two timestamps produces a timestamp in the time base of the left operand.
+#[derive(Copy,Clone,Debug)]
 pub struct Ts {
     index: i64,
     time_base: Timebase,
@@ -18,6 +51,24 @@ impl Ts {
         }
     }
 
+    /// Create a timestamp from a raw libav timestamp.
+    /// Returns `None` for `AV_NOPTS_VALUE`.
+    pub fn from_raw<TB: Into<Timebase>>(index: i64, time_base: TB) -> Option<Self> {
+        match index {
+            ffi::NOPTS_VALUE => None,
+            index => Some(Ts::new(index, time_base)),
+        }
+    }
+
+    /// Convert an optional timestamp to a raw libav timestamp in `time_base`.
+    /// `None` becomes `AV_NOPTS_VALUE`.
+    pub fn to_raw<TB: Into<Timebase>>(ts: Option<Ts>, time_base: TB) -> i64 {
+        match ts {
+            Some(ts) => ts.rescale(time_base, Rounding::NearInf).index(),
+            None => ffi::NOPTS_VALUE,
+        }
+    }
+
     pub fn index(&self) -> i64 {
         self.index
     }
@@ -26,16 +77,67 @@ impl Ts {
         self.time_base
     }
 
+    /// Convert the timestamp to another time base.
+    pub fn rescale<TB: Into<Timebase>>(&self, time_base: TB, rounding: Rounding) -> Ts {
+        let time_base = time_base.into();
+        let index = unsafe {
+            ffi::av_rescale_q_rnd(self.index, self.time_base.into(), time_base.into(), rounding.as_raw())
+        };
+
+        Ts::new(index, time_base)
+    }
+
+    /// Convert `seconds` to a timestamp in `time_base`,
+    /// rounding to the nearest index.
+    pub fn from_seconds<TB: Into<Timebase>>(seconds: f64, time_base: TB) -> Self {
+        let time_base = time_base.into();
+        let index = (seconds / time_base.as_f64()).round() as i64;
+
+        Ts::new(index, time_base)
+    }
+
+    /// The time since zero in seconds.
+    pub fn as_seconds(&self) -> f64 {
+        self.index as f64 * self.time_base.as_f64()
+    }
+
+    /// Convert `duration` to a timestamp in `time_base`,
+    /// rounding to the nearest index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the duration exceeds `i64::max_value()` nanoseconds (about 292 years).
+    pub fn from_duration<TB: Into<Timebase>>(duration: Duration, time_base: TB) -> Self {
+        let nanos = (duration.as_secs() as i64).checked_mul(NANOS_PER_SEC)
+            .and_then(|nanos| nanos.checked_add(duration.subsec_nanos() as i64))
+            .expect("Duration too long to be represented as a timestamp");
+
+        Ts::new(nanos, nanos_time_base()).rescale(time_base, Rounding::NearInf)
+    }
+
+    /// The time since zero, rounded to the nearest nanosecond.
+    /// Returns `None` if the timestamp is negative.
+    pub fn to_duration(&self) -> Option<Duration> {
+        let nanos = self.rescale(nanos_time_base(), Rounding::NearInf).index();
+
+        if nanos < 0 {
+            None
+        } else {
+            Some(Duration::new((nanos / NANOS_PER_SEC) as u64, (nanos % NANOS_PER_SEC) as u32))
+        }
+    }
+
+    /// Set the index to the one closest to the time passed since `stream_start`.
     pub fn calc_index_since(&mut self, stream_start: Instant) {
-        let duration = Instant::now().duration_since(stream_start);
-        let seconds = duration.as_secs();
-        let nanos = duration.subsec_nanos() as u64;
-        let duration = seconds * 1_000 + nanos / 1_000_000;
-        let index = duration as f64 * self.time_base.as_f64();
-        self.index = index.floor() as i64;
+        let elapsed = Instant::now().duration_since(stream_start);
+        self.index = Ts::from_duration(elapsed, self.time_base).index();
     }
 }
 
+fn nanos_time_base() -> Timebase {
+    Timebase::new(1, NANOS_PER_SEC as i32)
+}
+
 impl cmp::PartialEq for Ts {
     fn eq(&self, other: &Ts) -> bool {
         unsafe {
@@ -72,9 +174,47 @@ impl ops::AddAssign<i64> for Ts {
     }
 }
 
+impl ops::SubAssign<i64> for Ts {
+    fn sub_assign(&mut self, rhs: i64) {
+        self.index -= rhs;
+    }
+}
+
+/// `rhs` is rescaled to the time base of `self`, rounding to the nearest index.
+impl ops::Add for Ts {
+    type Output = Ts;
+
+    fn add(self, rhs: Ts) -> Ts {
+        let rhs = rhs.rescale(self.time_base, Rounding::NearInf);
+        Ts::new(self.index + rhs.index, self.time_base)
+    }
+}
+
+/// `rhs` is rescaled to the time base of `self`, rounding to the nearest index.
+impl ops::Sub for Ts {
+    type Output = Ts;
+
+    fn sub(self, rhs: Ts) -> Ts {
+        let rhs = rhs.rescale(self.time_base, Rounding::NearInf);
+        Ts::new(self.index - rhs.index, self.time_base)
+    }
+}
+
+impl ops::AddAssign for Ts {
+    fn add_assign(&mut self, rhs: Ts) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::SubAssign for Ts {
+    fn sub_assign(&mut self, rhs: Ts) {
+        *self = *self - rhs;
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Ts;
+    use super::{Ts, Rounding};
 
     #[test]
     fn index_since_instant() {
@@ -96,4 +236,45 @@ mod test {
             sleep(Duration::from_millis(1000 / fps as u64));
         }
     }
+
+    #[test]
+    fn duration_round_trip() {
+        use std::time::Duration;
+
+        let ts = Ts::from_duration(Duration::from_millis(1500), 90_000);
+        assert_eq!(ts.index(), 135_000);
+        assert_eq!(ts.to_duration(), Some(Duration::from_millis(1500)));
+
+        assert_eq!(Ts::new(-1, 25).to_duration(), None);
+    }
+
+    #[test]
+    fn rescale_rounding() {
+        let ts = Ts::new(3, (1, 2));
+
+        assert_eq!(ts.rescale(1, Rounding::Down).index(), 1);
+        assert_eq!(ts.rescale(1, Rounding::Up).index(), 2);
+        assert_eq!(ts.rescale(1, Rounding::NearInf).index(), 2);
+        assert_eq!(Ts::new(-3, (1, 2)).rescale(1, Rounding::Zero).index(), -1);
+    }
+
+    #[test]
+    fn arithmetic_across_time_bases() {
+        let a = Ts::new(90_000, 90_000);
+        let b = Ts::new(500, 1000);
+
+        let sum = a + b;
+        assert_eq!(sum.index(), 135_000);
+        assert_eq!(sum.time_base().den(), 90_000);
+        assert_eq!(b - a, Ts::from_seconds(-0.5, 1000));
+    }
+
+    #[test]
+    fn raw_nopts() {
+        use ffi::NOPTS_VALUE;
+
+        assert!(Ts::from_raw(NOPTS_VALUE, 25).is_none());
+        assert_eq!(Ts::to_raw(None, 25), NOPTS_VALUE);
+        assert_eq!(Ts::to_raw(Some(Ts::new(2, 1)), 25), 50);
+    }
 }
@@ -1,5 +1,7 @@
-use ffi::AVCodecID;
-use codec::MediaType;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use ffi;
+use codec::{CodecId, MediaType};
 
 error_chain! {
     errors {
@@ -13,31 +15,70 @@ error_chain! {
             display("Could not find decoder for {}", name)
         }
 
-        OpenEncoder(kind: &'static str) {
-            description("Could not open encoder")
-            display("Could not open {} encoder", kind)
-        }
-
-        OpenDecoder(kind: &'static str) {
-            description("Could not open decoder")
-            display("Could not open {} decoder", kind)
-        }
-
-        CopyCodecParameters
-
         AllocFailed(of: &'static str) {
             description("Failed to allocate something (OOM?)")
             display("Failed to allocate {} (OOM?)", of)
         }
 
-        EncodingUnsupported(codec_id: AVCodecID) {
+        EncodingUnsupported(codec_id: CodecId) {
             description("Codec does not support encoding")
-            display("{:?} codec does not support encoding", codec_id)
+            display("{} codec does not support encoding", codec_id)
         }
 
-        MediaTypeMismatch(encoder_type: MediaType, codec_id: AVCodecID) {
+        MediaTypeMismatch(encoder_type: MediaType, codec_id: CodecId) {
             description("Encoder and codec media types mismatch")
-            display("Cannot encode/decode {:?} using {:?} encoder/decoder", codec_id, encoder_type)
+            display("Cannot encode/decode {} using {:?} encoder/decoder", codec_id, encoder_type)
+        }
+
+        InvalidEncoderSetting(setting: &'static str, reason: String) {
+            description("Invalid encoder setting")
+            display("Invalid encoder setting {}: {}", setting, reason)
+        }
+
+        // A libav function returned the error `code` (e.g. `ffi::AVERROR_EAGAIN`).
+        // `message` is the description returned by `av_strerror`.
+        Av(code: c_int, message: String, operation: String) {
+            description("libav call failed")
+            display("{}: {} ({})", operation, message, code)
+        }
+    }
+}
+
+impl ErrorKind {
+    /// Create an `ErrorKind::Av` from the libav error `code`
+    /// returned while performing `operation`.
+    pub fn av<S: Into<String>>(code: c_int, operation: S) -> ErrorKind {
+        ErrorKind::Av(code, av_strerror(code), operation.into())
+    }
+
+    /// The libav error code, if this is an `Av` error.
+    pub fn av_code(&self) -> Option<c_int> {
+        match *self {
+            ErrorKind::Av(code, _, _) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Whether this is an `Av` error with the code `AVERROR(EAGAIN)`.
+    pub fn is_eagain(&self) -> bool {
+        self.av_code() == Some(ffi::AVERROR_EAGAIN)
+    }
+
+    /// Whether this is an `Av` error with the code `AVERROR_EOF`.
+    pub fn is_eof(&self) -> bool {
+        self.av_code() == Some(ffi::AVERROR_EOF)
+    }
+}
+
+/// Get the description of a libav error code.
+pub fn av_strerror(code: c_int) -> String {
+    unsafe {
+        let mut buf = [0 as c_char; ffi::AV_ERROR_MAX_STRING_SIZE as usize];
+        let res = ffi::av_strerror(code, buf.as_mut_ptr(), buf.len());
+        if res < 0 {
+            return format!("Unknown error code {}", code);
         }
+
+        CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()
     }
 }
@@ -0,0 +1,341 @@
+use std::ptr;
+use std::mem;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use LibAV;
+use ffi::{
+    self,
+    AVFilter,
+    AVFilterContext,
+    AVFilterGraph,
+    AVFilterInOut,
+};
+use video::PixelFormat;
+use audio::{ChannelLayout, SampleFormat};
+use common::Timebase;
+use errors::*;
+use super::{
+    VideoSource,
+    AudioSource,
+    VideoSink,
+    AudioSink,
+};
+
+/// A configured libavfilter graph.
+///
+/// Frames are fed into the graph through its sources
+/// and the filtered frames are taken out of its sinks.
+pub struct FilterGraph {
+    ptr: *mut AVFilterGraph,
+    video_sources: Vec<(String, VideoSource)>,
+    audio_sources: Vec<(String, AudioSource)>,
+    video_sinks: Vec<(String, VideoSink)>,
+    audio_sinks: Vec<(String, AudioSink)>,
+}
+
+unsafe impl Send for FilterGraph {}
+unsafe impl Sync for FilterGraph {}
+
+impl FilterGraph {
+    /// Start building a graph from a filter description like `"scale=640:-1,hflip"`.
+    ///
+    /// The sources and sinks added to the builder are linked to the
+    /// open inputs and outputs of the description by their names
+    /// (e.g. `[in]scale=640:-1[out]`). libavfilter labels an unlabeled first input
+    /// `in` and an unlabeled last output `out`, so labels can be omitted
+    /// for a single source named `in` and a single sink named `out`.
+    pub fn new(description: &str) -> Result<FilterGraphBuilder> {
+        FilterGraphBuilder::new(description)
+    }
+
+    pub fn video_source(&mut self, name: &str) -> Option<&mut VideoSource> {
+        self.video_sources.iter_mut().find(|entry| entry.0 == name).map(|entry| &mut entry.1)
+    }
+
+    pub fn audio_source(&mut self, name: &str) -> Option<&mut AudioSource> {
+        self.audio_sources.iter_mut().find(|entry| entry.0 == name).map(|entry| &mut entry.1)
+    }
+
+    pub fn video_sink(&mut self, name: &str) -> Option<&mut VideoSink> {
+        self.video_sinks.iter_mut().find(|entry| entry.0 == name).map(|entry| &mut entry.1)
+    }
+
+    pub fn audio_sink(&mut self, name: &str) -> Option<&mut AudioSink> {
+        self.audio_sinks.iter_mut().find(|entry| entry.0 == name).map(|entry| &mut entry.1)
+    }
+
+    /// Send a command to one or more filters of the running graph.
+    ///
+    /// `target` is either a filter instance name, a filter name or `"all"`.
+    /// Returns the response of the filter(s).
+    pub fn send_command(&mut self, target: &str, command: &str, arg: &str) -> Result<String> {
+        unsafe {
+            let target = CString::new(target).map_err(|_| "Filter command target contains nul byte")?;
+            let command = CString::new(command).map_err(|_| "Filter command contains nul byte")?;
+            let arg = CString::new(arg).map_err(|_| "Filter command argument contains nul byte")?;
+            let mut response = [0 as c_char; 256];
+            let flags = 0;
+
+            let res = ffi::avfilter_graph_send_command(
+                self.ptr,
+                target.as_ptr(),
+                command.as_ptr(),
+                arg.as_ptr(),
+                response.as_mut_ptr(),
+                response.len() as c_int,
+                flags
+            );
+
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Failed to send filter command {:?}", command)));
+            }
+
+            Ok(CStr::from_ptr(response.as_ptr()).to_string_lossy().into_owned())
+        }
+    }
+
+    /// Queue a command to be executed by the filter(s) once
+    /// a frame with a timestamp of at least `time` (in seconds) is processed.
+    pub fn queue_command(&mut self, target: &str, command: &str, arg: &str, time: f64) -> Result<()> {
+        unsafe {
+            let target = CString::new(target).map_err(|_| "Filter command target contains nul byte")?;
+            let command = CString::new(command).map_err(|_| "Filter command contains nul byte")?;
+            let arg = CString::new(arg).map_err(|_| "Filter command argument contains nul byte")?;
+            let flags = 0;
+
+            let res = ffi::avfilter_graph_queue_command(
+                self.ptr,
+                target.as_ptr(),
+                command.as_ptr(),
+                arg.as_ptr(),
+                flags,
+                time
+            );
+
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Failed to queue filter command {:?}", command)));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+impl FilterGraph {
+    pub fn as_ref(&self) -> &AVFilterGraph {
+        unsafe { &*self.ptr }
+    }
+    pub fn as_mut(&mut self) -> &mut AVFilterGraph {
+        unsafe { &mut *self.ptr }
+    }
+    pub fn as_ptr(&self) -> *const AVFilterGraph {
+        self.ptr
+    }
+    pub fn as_mut_ptr(&mut self) -> *mut AVFilterGraph {
+        self.ptr
+    }
+}
+
+impl Drop for FilterGraph {
+    fn drop(&mut self) {
+        unsafe {
+            // Also frees all sources and sinks
+            ffi::avfilter_graph_free(&mut self.ptr);
+        }
+    }
+}
+
+enum Endpoint {
+    VideoSource,
+    AudioSource,
+    VideoSink,
+    AudioSink,
+}
+
+pub struct FilterGraphBuilder {
+    ptr: *mut AVFilterGraph,
+    description: CString,
+    sources: Vec<(String, Endpoint, *mut AVFilterContext)>,
+    sinks: Vec<(String, Endpoint, *mut AVFilterContext)>,
+}
+
+impl FilterGraphBuilder {
+    pub fn new(description: &str) -> Result<FilterGraphBuilder> {
+        unsafe {
+            LibAV::init();
+
+            let description = CString::new(description)
+                .map_err(|_| "Filter description contains nul byte")?;
+
+            let graph = ffi::avfilter_graph_alloc();
+            if graph.is_null() {
+                bail!(ErrorKind::AllocFailed("filter graph"));
+            }
+
+            Ok(FilterGraphBuilder {
+                ptr: graph,
+                description: description,
+                sources: Vec::new(),
+                sinks: Vec::new(),
+            })
+        }
+    }
+
+    /// Add a source accepting video frames with the given properties.
+    /// `sample_aspect_ratio` is usually the one of the frames (e.g. `Frame::sample_aspect_ratio`);
+    /// `None` means unknown.
+    pub fn add_video_source<TB: Into<Timebase>>(&mut self,
+        name: &str, width: usize, height: usize, pixel_format: PixelFormat, time_base: TB,
+        sample_aspect_ratio: Option<Timebase>,
+    ) -> Result<()> {
+        let time_base = time_base.into();
+        let sample_aspect_ratio = sample_aspect_ratio.unwrap_or(Timebase::new(0, 1));
+        let args = format!("video_size={}x{}:pix_fmt={}:time_base={}/{}:pixel_aspect={}/{}",
+            width, height, pixel_format.as_raw() as c_int, time_base.num(), time_base.den(),
+            sample_aspect_ratio.num(), sample_aspect_ratio.den());
+        let context = self.create_filter("buffer", name, &args)?;
+        self.sources.push((name.to_string(), Endpoint::VideoSource, context));
+        Ok(())
+    }
+
+    /// Add a source accepting audio frames with the given properties.
+    pub fn add_audio_source(&mut self,
+        name: &str, sample_rate: u32, sample_format: SampleFormat, channel_layout: ChannelLayout,
+    ) -> Result<()> {
+        let args = format!("time_base=1/{}:sample_rate={}:sample_fmt={}:channel_layout=0x{:X}",
+            sample_rate, sample_rate, sample_format.as_raw() as c_int, channel_layout.bits());
+        let context = self.create_filter("abuffer", name, &args)?;
+        self.sources.push((name.to_string(), Endpoint::AudioSource, context));
+        Ok(())
+    }
+
+    /// Add a sink producing video frames.
+    pub fn add_video_sink(&mut self, name: &str) -> Result<()> {
+        let context = self.create_filter("buffersink", name, "")?;
+        self.sinks.push((name.to_string(), Endpoint::VideoSink, context));
+        Ok(())
+    }
+
+    /// Add a sink producing audio frames.
+    pub fn add_audio_sink(&mut self, name: &str) -> Result<()> {
+        let context = self.create_filter("abuffersink", name, "")?;
+        self.sinks.push((name.to_string(), Endpoint::AudioSink, context));
+        Ok(())
+    }
+
+    /// Parse the description, link it to the sources and sinks and configure the graph.
+    pub fn open(mut self) -> Result<FilterGraph> {
+        unsafe {
+            if self.sources.is_empty() || self.sinks.is_empty() {
+                bail!("Filter graph requires at least one source and one sink");
+            }
+
+            // The outputs of the sources are the inputs of the description and vice versa
+            let mut outputs = inout_list(&self.sources)?;
+            let mut inputs = match inout_list(&self.sinks) {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    ffi::avfilter_inout_free(&mut outputs);
+                    return Err(e);
+                }
+            };
+
+            let log_ctx = ptr::null_mut();
+            let res = ffi::avfilter_graph_parse_ptr(self.ptr, self.description.as_ptr(), &mut inputs, &mut outputs, log_ctx);
+            ffi::avfilter_inout_free(&mut inputs);
+            ffi::avfilter_inout_free(&mut outputs);
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Failed to parse filter description {:?}", self.description)));
+            }
+
+            let res = ffi::avfilter_graph_config(self.ptr, log_ctx);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Failed to configure filter graph"));
+            }
+
+            let mut graph = FilterGraph {
+                ptr: mem::replace(&mut self.ptr, ptr::null_mut()),
+                video_sources: Vec::new(),
+                audio_sources: Vec::new(),
+                video_sinks: Vec::new(),
+                audio_sinks: Vec::new(),
+            };
+
+            for (name, kind, context) in self.sources.drain(..).chain(self.sinks.drain(..)) {
+                match kind {
+                    Endpoint::VideoSource => graph.video_sources.push((name, VideoSource::from_ptr(context))),
+                    Endpoint::AudioSource => graph.audio_sources.push((name, AudioSource::from_ptr(context))),
+                    Endpoint::VideoSink   => graph.video_sinks.push((name, VideoSink::from_ptr(context))),
+                    Endpoint::AudioSink   => graph.audio_sinks.push((name, AudioSink::from_ptr(context))),
+                }
+            }
+
+            Ok(graph)
+        }
+    }
+
+    fn create_filter(&mut self, filter_name: &str, name: &str, args: &str) -> Result<*mut AVFilterContext> {
+        unsafe {
+            let filter = find_filter(filter_name)?;
+            let c_name = CString::new(name).map_err(|_| "Filter name contains nul byte")?;
+            let args = CString::new(args).unwrap();
+            let opaque = ptr::null_mut();
+            let mut context = ptr::null_mut();
+
+            if self.sources.iter().chain(&self.sinks).any(|&(ref n, _, _)| n == name) {
+                bail!("Filter graph already contains a source or sink named {:?}", name);
+            }
+
+            let res = ffi::avfilter_graph_create_filter(&mut context, filter, c_name.as_ptr(), args.as_ptr(), opaque, self.ptr);
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Could not create {} filter {:?}", filter_name, name)));
+            }
+
+            Ok(context)
+        }
+    }
+}
+
+impl Drop for FilterGraphBuilder {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ptr.is_null() {
+                ffi::avfilter_graph_free(&mut self.ptr);
+            }
+        }
+    }
+}
+
+unsafe impl Send for FilterGraphBuilder {}
+unsafe impl Sync for FilterGraphBuilder {}
+
+unsafe fn find_filter(name: &str) -> Result<*const AVFilter> {
+    let c_name = CString::new(name).unwrap();
+    let filter = ffi::avfilter_get_by_name(c_name.as_ptr());
+    if filter.is_null() {
+        bail!("Filter {:?} not found", name);
+    }
+    Ok(filter)
+}
+
+/// Build a linked `AVFilterInOut` list from the given endpoints.
+unsafe fn inout_list(endpoints: &[(String, Endpoint, *mut AVFilterContext)]) -> Result<*mut AVFilterInOut> {
+    let mut head: *mut AVFilterInOut = ptr::null_mut();
+
+    for &(ref name, _, context) in endpoints.iter().rev() {
+        let inout = ffi::avfilter_inout_alloc();
+        if inout.is_null() {
+            ffi::avfilter_inout_free(&mut head);
+            bail!(ErrorKind::AllocFailed("filter in/out"));
+        }
+
+        let c_name = CString::new(name.as_str()).unwrap();
+        (*inout).name = ffi::av_strdup(c_name.as_ptr());
+        (*inout).filter_ctx = context;
+        (*inout).pad_idx = 0;
+        (*inout).next = head;
+        head = inout;
+    }
+
+    Ok(head)
+}
@@ -0,0 +1,17 @@
+mod graph;
+pub use self::graph::{
+    FilterGraph,
+    FilterGraphBuilder,
+};
+
+mod source;
+pub use self::source::{
+    VideoSource,
+    AudioSource,
+};
+
+mod sink;
+pub use self::sink::{
+    VideoSink,
+    AudioSink,
+};
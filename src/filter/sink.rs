@@ -0,0 +1,110 @@
+use std::mem;
+use ffi::{
+    self,
+    AVFilterContext,
+    AVFrame,
+    AVPixelFormat,
+    AVSampleFormat,
+};
+use video::{self, PixelFormat};
+use audio::{self, SampleFormat};
+use errors::*;
+
+/// The output side of a filter graph producing video frames.
+pub struct VideoSink {
+    ptr: *mut AVFilterContext,
+}
+
+impl VideoSink {
+    pub unsafe fn from_ptr(ptr: *mut AVFilterContext) -> Self {
+        VideoSink { ptr: ptr }
+    }
+
+    /// Take a filtered frame out of the graph.
+    ///
+    /// Returns `None` if more input is required.
+    /// Once all frames have been received after flushing the sources,
+    /// an `Av` error is returned for which `is_eof()` holds.
+    pub fn receive(&mut self) -> Result<Option<video::Frame>> {
+        unsafe {
+            let frame = match get_frame(self.ptr)? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            let pixel_format: AVPixelFormat = mem::transmute((*frame).format);
+
+            Ok(Some(video::Frame::from_ptr(frame, PixelFormat::from_raw(pixel_format))))
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVFilterContext {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVFilterContext {
+        self.ptr
+    }
+}
+
+/// The output side of a filter graph producing audio frames.
+pub struct AudioSink {
+    ptr: *mut AVFilterContext,
+}
+
+impl AudioSink {
+    pub unsafe fn from_ptr(ptr: *mut AVFilterContext) -> Self {
+        AudioSink { ptr: ptr }
+    }
+
+    /// Take a filtered frame out of the graph.
+    ///
+    /// Returns `None` if more input is required.
+    /// Once all frames have been received after flushing the sources,
+    /// an `Av` error is returned for which `is_eof()` holds.
+    pub fn receive(&mut self) -> Result<Option<audio::Frame>> {
+        unsafe {
+            let frame = match get_frame(self.ptr)? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            let sample_format: AVSampleFormat = mem::transmute((*frame).format);
+
+            Ok(Some(audio::Frame::from_ptr(frame, SampleFormat::from_raw(sample_format))))
+        }
+    }
+
+    /// Make every received frame contain exactly `frame_size` samples
+    /// (except for the last one). Useful for encoders with a fixed frame size.
+    pub fn set_frame_size(&mut self, frame_size: usize) {
+        unsafe {
+            ffi::av_buffersink_set_frame_size(self.ptr, frame_size as u32);
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVFilterContext {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVFilterContext {
+        self.ptr
+    }
+}
+
+unsafe fn get_frame(sink: *mut AVFilterContext) -> Result<Option<*mut AVFrame>> {
+    let mut frame = ffi::av_frame_alloc();
+    if frame.is_null() {
+        bail!(ErrorKind::AllocFailed("filtered frame"));
+    }
+
+    let res = ffi::av_buffersink_get_frame(sink, frame);
+    if res < 0 {
+        ffi::av_frame_free(&mut frame);
+
+        match res {
+            ffi::AVERROR_EAGAIN => return Ok(None),
+            _ => bail!(ErrorKind::av(res, "Failed to receive frame from filter graph")),
+        }
+    }
+
+    Ok(Some(frame))
+}
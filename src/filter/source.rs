@@ -0,0 +1,87 @@
+use ffi::{
+    self,
+    AVFilterContext,
+    AV_BUFFERSRC_FLAG_KEEP_REF,
+};
+use std::ptr;
+use video;
+use audio;
+use errors::*;
+
+/// The input side of a filter graph accepting video frames.
+pub struct VideoSource {
+    ptr: *mut AVFilterContext,
+}
+
+impl VideoSource {
+    pub unsafe fn from_ptr(ptr: *mut AVFilterContext) -> Self {
+        VideoSource { ptr: ptr }
+    }
+
+    /// Feed a frame into the filter graph.
+    /// The frame is referenced, not consumed.
+    pub fn send(&mut self, frame: &video::Frame) -> Result<()> {
+        unsafe {
+            add_frame(self.ptr, frame.as_ptr() as _)
+        }
+    }
+
+    /// Signal that no more frames will be sent to this source.
+    pub fn flush(&mut self) -> Result<()> {
+        unsafe {
+            add_frame(self.ptr, ptr::null_mut())
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVFilterContext {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVFilterContext {
+        self.ptr
+    }
+}
+
+/// The input side of a filter graph accepting audio frames.
+pub struct AudioSource {
+    ptr: *mut AVFilterContext,
+}
+
+impl AudioSource {
+    pub unsafe fn from_ptr(ptr: *mut AVFilterContext) -> Self {
+        AudioSource { ptr: ptr }
+    }
+
+    /// Feed a frame into the filter graph.
+    /// The frame is referenced, not consumed.
+    pub fn send(&mut self, frame: &audio::Frame) -> Result<()> {
+        unsafe {
+            add_frame(self.ptr, frame.as_ptr() as _)
+        }
+    }
+
+    /// Signal that no more frames will be sent to this source.
+    pub fn flush(&mut self) -> Result<()> {
+        unsafe {
+            add_frame(self.ptr, ptr::null_mut())
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const AVFilterContext {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVFilterContext {
+        self.ptr
+    }
+}
+
+unsafe fn add_frame(source: *mut AVFilterContext, frame: *mut ffi::AVFrame) -> Result<()> {
+    let flags = AV_BUFFERSRC_FLAG_KEEP_REF as i32;
+    let res = ffi::av_buffersrc_add_frame_flags(source, frame, flags);
+    if res < 0 {
+        bail!(ErrorKind::av(res, "Failed to feed frame into filter graph"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::slice;
+use std::fmt;
+use ffi::{AVChapter, AVFormatContext};
+use common::{Timebase, Ts, Metadata};
+
+/// A chapter of a demuxed file, e.g. of an audiobook.
+pub struct Chapter<'fmt_ctx> {
+    ptr: *const AVChapter,
+    _phantom: PhantomData<&'fmt_ctx AVFormatContext>,
+}
+
+impl<'fmt_ctx> Chapter<'fmt_ctx> {
+    pub unsafe fn from_ptr(ptr: *const AVChapter) -> Chapter<'fmt_ctx> {
+        Chapter {
+            ptr: ptr,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The unique ID of the chapter.
+    pub fn id(&self) -> c_int {
+        self.as_ref().id
+    }
+
+    pub fn time_base(&self) -> Timebase {
+        self.as_ref().time_base.into()
+    }
+
+    pub fn start(&self) -> Ts {
+        Ts::new(self.as_ref().start, self.time_base())
+    }
+
+    pub fn end(&self) -> Ts {
+        Ts::new(self.as_ref().end, self.time_base())
+    }
+
+    pub fn metadata(&self) -> Metadata<'fmt_ctx> {
+        unsafe {
+            Metadata::from_ptr(self.as_ref().metadata)
+        }
+    }
+
+    /// The title from the metadata.
+    pub fn title(&self) -> Option<&'fmt_ctx CStr> {
+        self.metadata().get("title")
+    }
+
+    pub fn as_ref(&self) -> &'fmt_ctx AVChapter {
+        unsafe { &*self.ptr }
+    }
+
+    pub fn as_ptr(&self) -> *const AVChapter {
+        self.ptr
+    }
+}
+
+impl<'fmt_ctx> fmt::Debug for Chapter<'fmt_ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chapter")
+            .field("id", &self.id())
+            .field("start", &self.start().to_duration())
+            .field("end", &self.end().to_duration())
+            .field("title", &self.title())
+            .finish()
+    }
+}
+
+pub struct Chapters<'fmt_ctx> {
+    iter: slice::Iter<'fmt_ctx, *mut AVChapter>,
+}
+
+impl<'fmt_ctx> Chapters<'fmt_ctx> {
+    pub unsafe fn from_slice(slice: &'fmt_ctx [*mut AVChapter]) -> Chapters<'fmt_ctx> {
+        Chapters {
+            iter: slice.iter()
+        }
+    }
+}
+
+impl<'fmt_ctx> Iterator for Chapters<'fmt_ctx> {
+    type Item = Chapter<'fmt_ctx>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            self.iter.next().map(|&ptr| Chapter::from_ptr(ptr))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'fmt_ctx> ExactSizeIterator for Chapters<'fmt_ctx> {}
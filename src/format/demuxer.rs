@@ -1,18 +1,24 @@
 use std::ptr;
 use std::fmt;
 use std::ffi::CStr;
+use std::os::raw::c_int;
 use LibAV;
 use io;
 use ffi;
 use ffi::{
     AVFormatContext,
     AVInputFormat,
-    AV_TIME_BASE,
+    AVPacket,
+    AV_PKT_FLAG_KEY,
 };
 use util::AsCStr;
 use errors::*;
-use common::stream::Streams;
-use common::Packet;
+use common::stream::{Stream, Streams};
+use common::{Packet, Ts, Timebase, Rounding, AV_TIME_BASE_Q, Metadata, Options, Discard};
+use codec::{Codec, MediaType};
+use common::options;
+use format::{SeekFlags, Chapters};
+use format::{SEEK_BYTE, SEEK_FRAME};
 use std::slice;
 
 pub struct Demuxer {
@@ -20,52 +26,83 @@ pub struct Demuxer {
     // The io context is borrowed by the format context
     // and is kept around to be dropped at the right time.
     _io_context: io::IOContext,
+    unused_options: Vec<String>,
 }
 
 impl Demuxer {
     pub fn open<W: io::AVRead>(reader: W) -> Result<Demuxer> {
+        Self::open_with_options(reader, &Options::new())
+    }
+
+    /// Open the demuxer using the given format options (e.g. `probesize`).
+    ///
+    /// Options not consumed by the demuxer are passed on as codec options
+    /// when probing the streams. Options used by neither are reported by `unused_options`.
+    pub fn open_with_options<W: io::AVRead>(reader: W, options: &Options) -> Result<Demuxer> {
         unsafe {
             LibAV::init();
 
             // Allocate IOContext and AVFormatContext
-            let mut io_context = io::IOContext::from_reader(reader);
+            let mut io_context = io::IOContext::from_reader(reader)?;
             let mut format_context = ffi::avformat_alloc_context();
 
             if format_context.is_null() {
-                bail!("Failed to allocate input context");
+                bail!(ErrorKind::AllocFailed("input context"));
             }
 
             // Lend the io context to the format context
             (*format_context).pb = io_context.as_mut_ptr();
 
+            let mut options = match options.to_dict() {
+                Ok(options) => options,
+                Err(err) => {
+                    ffi::avformat_free_context(format_context);
+                    return Err(err);
+                },
+            };
+
             // Open the demuxer
             {
                 let url = ptr::null_mut();
                 let format = ptr::null_mut();
-                let options = ptr::null_mut();
-                let res = ffi::avformat_open_input(&mut format_context, url, format, options);
+                let res = ffi::avformat_open_input(&mut format_context, url, format, &mut options);
 
                 if res < 0 {
+                    ffi::av_dict_free(&mut options);
                     // No need to fre format_context here.
                     // avformat_open_input already has freed the format context at this point.
-                    bail!("Failed to open input context");
+                    bail!(ErrorKind::av(res, "Failed to open input context"));
                 }
             }
 
             // Decode some stream info
-            {
-                let options = ptr::null_mut();
-                let res = ffi::avformat_find_stream_info(format_context, options);
+            let unused_options = {
+                // Every stream gets its own copy of the remaining options
+                let num_streams = (*format_context).nb_streams as usize;
+                let mut stream_options = (0..num_streams)
+                    .map(|_| {
+                        let mut stream_options = ptr::null_mut();
+                        ffi::av_dict_copy(&mut stream_options, options, 0);
+                        stream_options
+                    })
+                    .collect::<Vec<_>>();
+                let stream_options_ptr = if num_streams > 0 { stream_options.as_mut_ptr() } else { ptr::null_mut() };
+
+                let res = ffi::avformat_find_stream_info(format_context, stream_options_ptr);
+                let unused_options = options::take_unused_by_all(&mut options, &mut stream_options);
 
                 if res < 0 {
                     ffi::avformat_close_input(&mut format_context);
-                    bail!("Failed to find stream info");
+                    bail!(ErrorKind::av(res, "Failed to find stream info"));
                 }
-            }
+
+                unused_options
+            };
 
             Ok(Demuxer {
                 ptr: format_context,
                 _io_context: io_context,
+                unused_options: unused_options,
             })
         }
     }
@@ -81,14 +118,95 @@ impl Demuxer {
         unsafe { (*self.ptr).nb_streams as usize }
     }
 
-    /// Duration in seconds (floored)
-    /// TODO: Return a more exact/fexible representation
-    pub fn duration(&self) -> u32 {
-        let duration = unsafe { (*self.ptr).duration };
-        if duration <= 0 {
-            return 0;
-        } else {
-            duration as u32 / AV_TIME_BASE
+    pub fn num_chapters(&self) -> usize {
+        unsafe { (*self.ptr).nb_chapters as usize }
+    }
+
+    /// The chapters, ordered by start time for most formats.
+    pub fn chapters(&self) -> Chapters {
+        unsafe {
+            let chapters = self.as_ref().chapters;
+            let num_chapters = self.as_ref().nb_chapters as usize;
+
+            if chapters.is_null() {
+                Chapters::from_slice(&[])
+            } else {
+                Chapters::from_slice(slice::from_raw_parts(chapters, num_chapters))
+            }
+        }
+    }
+
+    /// Find the most suitable stream of `media_type` (e.g. the default audio track)
+    /// together with a decoder for it.
+    ///
+    /// If `related_stream` is given, streams related to it
+    /// (e.g. in the same program) are preferred.
+    /// Streams without a decoder are skipped.
+    /// Returns `None` if there is no stream of `media_type` or none of them has a decoder.
+    pub fn best_stream(&self, media_type: MediaType, related_stream: Option<usize>) -> Result<Option<(Stream, Codec)>> {
+        unsafe {
+            let wanted_stream = -1;
+            let related_stream = related_stream.map(|index| index as c_int).unwrap_or(-1);
+            let mut decoder = ptr::null_mut();
+            let flags = 0;
+
+            let res = ffi::av_find_best_stream(self.ptr, media_type.as_raw(), wanted_stream, related_stream, &mut decoder, flags);
+            match res {
+                ffi::AVERROR_STREAM_NOT_FOUND | ffi::AVERROR_DECODER_NOT_FOUND => Ok(None),
+                _ if res < 0 => bail!(ErrorKind::av(res, format!("Failed to find a {:?} stream", media_type))),
+                index => {
+                    let stream = self.streams().nth(index as usize)
+                        .ok_or("av_find_best_stream returned an invalid stream index")?;
+
+                    Ok(Some((stream, Codec::from_ptr(decoder))))
+                },
+            }
+        }
+    }
+
+    /// Set which packets of the stream with the given index should be dropped.
+    /// With `Discard::All`, `read_packet` doesn't return any packets of the stream.
+    pub fn set_discard(&mut self, stream_index: usize, discard: Discard) -> Result<()> {
+        if stream_index >= self.num_streams() {
+            bail!("Invalid stream index {}. Only {} stream(s) exist(s).", stream_index, self.num_streams());
+        }
+
+        unsafe {
+            let stream = *(*self.ptr).streams.offset(stream_index as isize);
+            (*stream).discard = discard.as_raw();
+        }
+
+        Ok(())
+    }
+
+    /// The duration in `AV_TIME_BASE_Q`, if known.
+    /// Use `Ts::to_duration` to convert it to a `Duration`.
+    pub fn duration(&self) -> Option<Ts> {
+        Ts::from_raw(unsafe { (*self.ptr).duration }, AV_TIME_BASE_Q)
+    }
+
+    /// The presentation timestamp of the first frame in `AV_TIME_BASE_Q`, if known.
+    pub fn start_time(&self) -> Option<Ts> {
+        Ts::from_raw(unsafe { (*self.ptr).start_time }, AV_TIME_BASE_Q)
+    }
+
+    /// The total bit rate in bit/s, if known.
+    pub fn bit_rate(&self) -> Option<i64> {
+        match unsafe { (*self.ptr).bit_rate } {
+            0 => None,
+            bit_rate => Some(bit_rate),
+        }
+    }
+
+    /// Options passed to `open_with_options` that were not consumed.
+    pub fn unused_options(&self) -> &[String] {
+        &self.unused_options
+    }
+
+    /// Container-level metadata
+    pub fn metadata(&self) -> Metadata {
+        unsafe {
+            Metadata::from_ptr(self.as_ref().metadata)
         }
     }
 
@@ -120,17 +238,23 @@ impl Demuxer {
                 bail!(ErrorKind::AllocFailed("demuxing packet"));
             }
 
-            // Try to read a packet
-            {
+            // Try to read a packet, skipping discarded ones
+            loop {
                 let res = ffi::av_read_frame(self.ptr, packet);
                 if res < 0 {
                     ffi::av_packet_free(&mut packet);
 
                     match res {
                         ffi::AVERROR_EOF => return Ok(None),
-                        _ => bail!("Demuxer failed to read packet"),
+                        _ => bail!(ErrorKind::av(res, "Demuxer failed to read packet")),
                     }
                 }
+
+                if !self.is_discarded(&*packet) {
+                    break;
+                }
+
+                ffi::av_packet_unref(packet);
             }
 
             let time_base = self.streams().nth((*packet).stream_index as usize).map(|stream| stream.time_base())
@@ -139,6 +263,66 @@ impl Demuxer {
             Ok(Some(Packet::from_ptr(packet, time_base.into())))
         }
     }
+
+    /// Most demuxers skip discarded packets themselves, but not all of them do.
+    fn is_discarded(&self, packet: &AVPacket) -> bool {
+        let discard = match self.streams().nth(packet.stream_index as usize) {
+            Some(stream) => stream.discard(),
+            None => return false,
+        };
+        let is_key = packet.flags & AV_PKT_FLAG_KEY as c_int != 0;
+
+        match discard {
+            Discard::All => true,
+            Discard::NonKey => !is_key,
+            _ => false,
+        }
+    }
+
+    /// Whether the underlying reader supports seeking.
+    pub fn is_seekable(&self) -> bool {
+        self._io_context.is_seekable()
+    }
+
+    /// Seek to `ts` using the default stream.
+    ///
+    /// The timestamp is rescaled to the demuxer's time base.
+    /// With `SEEK_BYTE` or `SEEK_FRAME` the index of `ts` is used as is.
+    pub fn seek(&mut self, ts: Ts, flags: SeekFlags) -> Result<()> {
+        self.seek_raw(-1, ts, AV_TIME_BASE_Q, flags)
+    }
+
+    /// Seek to `ts` in the stream with the given index.
+    ///
+    /// The timestamp is rescaled to the stream's time base.
+    /// With `SEEK_BYTE` or `SEEK_FRAME` the index of `ts` is used as is.
+    pub fn seek_stream(&mut self, stream_index: usize, ts: Ts, flags: SeekFlags) -> Result<()> {
+        let time_base = self.streams().nth(stream_index).map(|stream| stream.time_base())
+            .ok_or_else(|| format!("Invalid stream index {}. Only {} stream(s) exist(s).", stream_index, self.num_streams()))?;
+
+        self.seek_raw(stream_index as i32, ts, time_base, flags)
+    }
+
+    fn seek_raw(&mut self, stream_index: i32, ts: Ts, time_base: Timebase, flags: SeekFlags) -> Result<()> {
+        unsafe {
+            if !self.is_seekable() {
+                bail!("Cannot seek: the demuxer's reader does not support seeking");
+            }
+
+            let index = if flags.intersects(SEEK_BYTE | SEEK_FRAME) {
+                ts.index()
+            } else {
+                ts.rescale(time_base, Rounding::NearInf).index()
+            };
+
+            let res = ffi::av_seek_frame(self.ptr, stream_index, index, flags.bits());
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Failed to seek to {}", index)));
+            }
+
+            Ok(())
+        }
+    }
 }
 
 impl Demuxer {
@@ -174,7 +358,7 @@ impl fmt::Debug for Demuxer {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Demuxer")
             .field("num_streams", &self.num_streams())
-            .field("duration", &format!("{} seconds", self.duration()))
+            .field("duration", &self.duration().and_then(|duration| duration.to_duration()))
             .field("format_name", &self.format_name())
             .field("format_long_name", &self.format_long_name())
             .finish()
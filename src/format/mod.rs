@@ -10,5 +10,20 @@ pub use self::demuxer::{
     Demuxer,
 };
 
+mod chapter;
+pub use self::chapter::{
+    Chapter,
+    Chapters,
+};
+
 mod output_format;
 pub use self::output_format::OutputFormat;
+
+mod seek;
+pub use self::seek::{
+    SeekFlags,
+    SEEK_BACKWARD,
+    SEEK_BYTE,
+    SEEK_ANY,
+    SEEK_FRAME,
+};
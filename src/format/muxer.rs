@@ -1,17 +1,18 @@
 use std::ptr;
+use std::slice;
 use std::mem;
 use std::fmt;
 use std::ffi::{CStr,CString};
-use std::os::raw::c_uint;
+use std::os::raw::{c_int, c_uint, c_void};
 use LibAV;
 use io;
 use ffi;
 use ffi::{
+    AVChapter,
     AVFormatContext,
     AVOutputFormat,
     AVPacket,
     AVStream,
-    AV_TIME_BASE,
     AVFMT_GLOBALHEADER,
     AV_CODEC_FLAG_GLOBAL_HEADER,
     AV_CODEC_CAP_DELAY,
@@ -23,9 +24,11 @@ use generic::{
     RefMutFrame,
     Packets,
 };
-use format::OutputFormat;
+use format::{OutputFormat, Chapter};
 use util::AsCStr;
-use common::Packet;
+use common::{Packet, Ts, Timebase, Rounding, AV_TIME_BASE_Q, Metadata, MetadataMut, Options};
+use common::options;
+use common::codec_parameters::CodecParameters;
 use errors::*;
 
 pub struct Muxer {
@@ -35,6 +38,7 @@ pub struct Muxer {
     _io_context: io::IOContext,
     // Whether muxer was closed explicitly
     closed: bool,
+    unused_options: Vec<String>,
 }
 
 unsafe impl Send for Muxer{}
@@ -49,14 +53,21 @@ impl Muxer {
         unsafe { (*self.ptr).nb_streams as usize }
     }
 
-    /// Duration in seconds (floored)
-    /// TODO: Return a more exact/fexible representation
-    pub fn duration(&self) -> u32 {
-        let duration = unsafe { (*self.ptr).duration };
-        if duration <= 0 {
-            return 0;
-        } else {
-            duration as u32 / AV_TIME_BASE
+    /// The duration in `AV_TIME_BASE_Q`, if known.
+    /// Use `Ts::to_duration` to convert it to a `Duration`.
+    pub fn duration(&self) -> Option<Ts> {
+        Ts::from_raw(unsafe { (*self.ptr).duration }, AV_TIME_BASE_Q)
+    }
+
+    /// Options passed to `MuxerBuilder::open_with_options` that were not consumed.
+    pub fn unused_options(&self) -> &[String] {
+        &self.unused_options
+    }
+
+    /// Container-level metadata
+    pub fn metadata(&self) -> Metadata {
+        unsafe {
+            Metadata::from_ptr(self.as_ref().metadata)
         }
     }
 
@@ -98,7 +109,7 @@ impl Muxer {
 
             let res = ffi::av_interleaved_write_frame(self.ptr, packet);
             if res < 0 {
-                bail!("Failed to write packet for stream {}: 0x{:X}", stream_index, res);
+                bail!(ErrorKind::av(res, format!("Failed to write packet for stream {}", stream_index)));
             }
 
             Ok(())
@@ -123,7 +134,7 @@ impl Muxer {
             {
                 let res = ffi::av_write_trailer(self.as_mut_ptr());
                 if res < 0 {
-                    bail!("Failed to write trailer: 0x{:X}", res);
+                    bail!(ErrorKind::av(res, "Failed to write trailer"));
                 }
             }
 
@@ -169,7 +180,7 @@ impl fmt::Debug for Muxer {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Muxer")
             .field("num_streams", &self.num_streams())
-            .field("duration", &format!("{} seconds", self.duration()))
+            .field("duration", &self.duration().and_then(|duration| duration.to_duration()))
             .field("format_name", &self.format_name())
             .field("format_long_name", &self.format_long_name())
             .finish()
@@ -187,7 +198,7 @@ impl MuxerBuilder {
             LibAV::init();
 
             let mut muxer = ptr::null_mut();
-            let mut io_context = io::IOContext::from_writer(writer);
+            let mut io_context = io::IOContext::from_writer(writer)?;
 
             // Allocate muxer
             {
@@ -196,8 +207,12 @@ impl MuxerBuilder {
 
                 let res = ffi::avformat_alloc_output_context2(&mut muxer, format.as_mut_ptr(), format_name, name);
 
-                if res < 0 || muxer.is_null() {
-                    bail!("Failed to allocate output context: 0x{:X}", res);
+                if res < 0 {
+                    bail!(ErrorKind::av(res, "Failed to allocate output context"));
+                }
+
+                if muxer.is_null() {
+                    bail!(ErrorKind::AllocFailed("output context"));
                 }
             }
 
@@ -229,7 +244,7 @@ impl MuxerBuilder {
             // Create stream context
             let stream = ffi::avformat_new_stream(self.ptr, encoder.as_ref().codec);
             if stream.is_null() {
-                bail!("Could not allocate stream")
+                bail!(ErrorKind::AllocFailed("stream"))
             }
 
             (*stream).id = (*self.ptr).nb_streams as i32 - 1;
@@ -240,7 +255,7 @@ impl MuxerBuilder {
             {
                 let res = ffi::avcodec_parameters_from_context((*stream).codecpar, encoder.as_ref());
                 if res < 0 {
-                    bail!("Could not copy stream parameters ({})", res)
+                    bail!(ErrorKind::av(res, "Could not copy stream parameters"))
                 }
             }
 
@@ -248,22 +263,150 @@ impl MuxerBuilder {
         }
     }
 
-    pub fn open(mut self) -> Result<Muxer> {
+    /// Add a new stream using the given codec parameters, e.g. from a demuxed stream.
+    /// This allows muxing packets without decoding/encoding them (stream copy).
+    pub fn add_stream_from_parameters<TB: Into<Timebase>>(&mut self, codec_parameters: &CodecParameters, time_base: TB) -> Result<()> {
         unsafe {
-            // Write header 
+            // Create stream context
+            let stream = ffi::avformat_new_stream(self.ptr, ptr::null());
+            if stream.is_null() {
+                bail!(ErrorKind::AllocFailed("stream"))
+            }
+
+            (*stream).id = (*self.ptr).nb_streams as i32 - 1;
+            (*stream).time_base = time_base.into().into();
+
+            // Copy codec parameters to stream
             {
-                let options = ptr::null_mut();
-                let res = ffi::avformat_write_header(self.ptr, options);
+                let res = ffi::avcodec_parameters_copy((*stream).codecpar, codec_parameters.as_ptr());
                 if res < 0 {
-                    ffi::avformat_free_context(self.ptr);
-                    bail!("Could not write header: 0x{:X}", res);
+                    bail!(ErrorKind::av(res, "Could not copy stream parameters"))
                 }
             }
 
+            // The codec tag of the source container might be invalid for the
+            // target container. Let the muxer choose a suitable one instead.
+            (*(*stream).codecpar).codec_tag = 0;
+
+            Ok(())
+        }
+    }
+
+    /// Container-level metadata written by `open`.
+    pub fn metadata_mut(&mut self) -> MetadataMut {
+        unsafe {
+            MetadataMut::from_ptr(&mut (*self.ptr).metadata)
+        }
+    }
+
+    /// Metadata of the stream with the given index, written by `open`.
+    pub fn stream_metadata_mut(&mut self, stream_index: usize) -> Option<MetadataMut> {
+        unsafe {
+            if stream_index >= (*self.ptr).nb_streams as usize {
+                return None;
+            }
+
+            let stream = *(*self.ptr).streams.offset(stream_index as isize);
+            Some(MetadataMut::from_ptr(&mut (*stream).metadata))
+        }
+    }
+
+    /// Add a chapter with a unique `id`, written by `open` (e.g. for MP4 or Matroska).
+    /// The chapter uses the time base of `start`; `end` is rescaled to it.
+    ///
+    /// Returns the metadata of the chapter, e.g. to set its `title`.
+    pub fn add_chapter(&mut self, id: c_int, start: Ts, end: Ts) -> Result<MetadataMut> {
+        unsafe {
+            let chapter = self.new_chapter(id, start, end)?;
+            Ok(MetadataMut::from_ptr(&mut (*chapter).metadata))
+        }
+    }
+
+    /// Add a copy of a demuxed chapter including its metadata, written by `open`.
+    pub fn add_chapter_from(&mut self, chapter: &Chapter) -> Result<()> {
+        unsafe {
+            let new_chapter = self.new_chapter(chapter.id(), chapter.start(), chapter.end())?;
+
+            let res = ffi::av_dict_copy(&mut (*new_chapter).metadata, chapter.as_ref().metadata, 0);
+            if res < 0 {
+                bail!(ErrorKind::av(res, format!("Could not copy metadata of chapter {}", chapter.id())));
+            }
+
+            Ok(())
+        }
+    }
+
+    unsafe fn new_chapter(&mut self, id: c_int, start: Ts, end: Ts) -> Result<*mut AVChapter> {
+        let time_base = start.time_base();
+        let end = end.rescale(time_base, Rounding::NearInf);
+
+        if end.index() < start.index() {
+            bail!("Chapter {} ends before it starts", id);
+        }
+
+        let num_chapters = (*self.ptr).nb_chapters as usize;
+        if num_chapters > 0 {
+            let chapters = slice::from_raw_parts((*self.ptr).chapters, num_chapters);
+            if chapters.iter().any(|&chapter| (*chapter).id == id) {
+                bail!("A chapter with id {} already exists", id);
+            }
+        }
+
+        let chapter = ffi::av_mallocz(mem::size_of::<AVChapter>()) as *mut AVChapter;
+        if chapter.is_null() {
+            bail!(ErrorKind::AllocFailed("chapter"))
+        }
+
+        let chapters = ffi::av_realloc_array(
+            (*self.ptr).chapters as *mut c_void,
+            num_chapters + 1,
+            mem::size_of::<*mut AVChapter>()
+        ) as *mut *mut AVChapter;
+        if chapters.is_null() {
+            ffi::av_free(chapter as *mut c_void);
+            bail!(ErrorKind::AllocFailed("chapters"))
+        }
+
+        (*chapter).id = id;
+        (*chapter).time_base = time_base.into();
+        (*chapter).start = start.index();
+        (*chapter).end = end.index();
+
+        // The chapters are freed together with the format context
+        *chapters.offset(num_chapters as isize) = chapter;
+        (*self.ptr).chapters = chapters;
+        (*self.ptr).nb_chapters += 1;
+
+        Ok(chapter)
+    }
+
+    pub fn open(self) -> Result<Muxer> {
+        self.open_with_options(&Options::new())
+    }
+
+    /// Write the header using the given muxer options (e.g. `movflags=+faststart`).
+    /// Options not consumed by the muxer are reported by `Muxer::unused_options`.
+    pub fn open_with_options(mut self, options: &Options) -> Result<Muxer> {
+        unsafe {
+            // Write header 
+            let unused_options = {
+                let mut options = options.to_dict()?;
+                let res = ffi::avformat_write_header(self.ptr, &mut options);
+                let unused_options = options::take_unused(&mut options);
+                if res < 0 {
+                    ffi::avformat_free_context(self.ptr);
+                    // Prevent double free on drop
+                    self.ptr = ptr::null_mut();
+                    bail!(ErrorKind::av(res, "Could not write header"));
+                }
+                unused_options
+            };
+
             Ok(Muxer {
                 ptr: mem::replace(&mut self.ptr, ptr::null_mut()),
                 _io_context: self.io_context.take().unwrap(),
                 closed: false,
+                unused_options: unused_options,
             })
         }
     }
@@ -282,3 +425,50 @@ impl Drop for MuxerBuilder {
 
 unsafe impl Send for MuxerBuilder {}
 unsafe impl Sync for MuxerBuilder {}
+
+#[cfg(test)]
+mod test {
+    use LibAV;
+    use codec::{Codec, CodecId};
+    use common::Ts;
+    use format::{Demuxer, Muxer, OutputFormat};
+    use io::MemoryBuffer;
+    use video;
+
+    #[test]
+    fn chapters_round_trip() {
+        LibAV::init();
+
+        let format = OutputFormat::from_name("matroska").unwrap();
+        let codec = Codec::find_encoder_by_id(CodecId::Ffv1).unwrap();
+        let encoder = video::Encoder::from_codec(codec).unwrap()
+            .width(16)
+            .height(16)
+            .pixel_format(video::PixelFormat::Yuv420p)
+            .open(format)
+            .unwrap();
+
+        let buffer = MemoryBuffer::new();
+        let mut muxer = Muxer::new(format, buffer.clone()).unwrap();
+        muxer.add_stream_from_encoder(&encoder).unwrap();
+        muxer.add_chapter(1, Ts::new(0, (1, 1000)), Ts::new(1500, (1, 1000))).unwrap()
+            .set("title", "Intro").unwrap();
+        muxer.add_chapter(2, Ts::new(3, (1, 2)), Ts::new(5, (1, 1))).unwrap();
+        assert!(muxer.add_chapter(2, Ts::new(5, (1, 1)), Ts::new(6, (1, 1))).is_err());
+        assert!(muxer.add_chapter(3, Ts::new(2, (1, 1)), Ts::new(1, (1, 1))).is_err());
+        muxer.open().unwrap().close().unwrap();
+
+        let demuxer = Demuxer::open(MemoryBuffer::from_vec(buffer.take())).unwrap();
+        let chapters: Vec<_> = demuxer.chapters().collect();
+
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].id(), 1);
+        assert_eq!(chapters[0].start().as_seconds(), 0.0);
+        assert_eq!(chapters[0].end().as_seconds(), 1.5);
+        assert_eq!(chapters[0].title().unwrap().to_str(), Ok("Intro"));
+        assert_eq!(chapters[1].id(), 2);
+        assert_eq!(chapters[1].start().as_seconds(), 1.5);
+        assert_eq!(chapters[1].end().as_seconds(), 5.0);
+        assert_eq!(chapters[1].title(), None);
+    }
+}
@@ -0,0 +1,20 @@
+use std::os::raw::c_int;
+use ffi::{
+    AVSEEK_FLAG_BACKWARD,
+    AVSEEK_FLAG_BYTE,
+    AVSEEK_FLAG_ANY,
+    AVSEEK_FLAG_FRAME,
+};
+
+bitflags! {
+    pub flags SeekFlags: c_int {
+        /// Seek to the nearest keyframe before the target.
+        const SEEK_BACKWARD = AVSEEK_FLAG_BACKWARD as c_int,
+        /// Interpret the timestamp index as a byte offset.
+        const SEEK_BYTE     = AVSEEK_FLAG_BYTE     as c_int,
+        /// Seek to any frame, even non-keyframes.
+        const SEEK_ANY      = AVSEEK_FLAG_ANY      as c_int,
+        /// Interpret the timestamp index as a frame number.
+        const SEEK_FRAME    = AVSEEK_FLAG_FRAME    as c_int,
+    }
+}
@@ -8,7 +8,7 @@ use video;
 use audio;
 use errors::*;
 use common::stream::Stream;
-use common::{Packet, Timebase};
+use common::{Packet, Timebase, Options};
 use codec::MediaType;
 use super::Frame;
 
@@ -27,6 +27,14 @@ impl Decoder {
         })
     }
 
+    pub fn from_stream_with_options(stream: &Stream, options: &Options) -> Result<Self> {
+        Ok(match stream.codec_parameters().media_type() {
+            MediaType::Video => video::Decoder::from_stream_with_options(stream, options)?.into(),
+            MediaType::Audio => audio::Decoder::from_stream_with_options(stream, options)?.into(),
+            other => bail!("Unsupported media type: {:?}", other)
+        })
+    }
+
     pub fn into_video_encoder(self) -> Option<video::Decoder> {
         match self {
             Decoder::Video(decoder) => Some(decoder),
@@ -82,6 +90,13 @@ impl Decoder {
             Decoder::Audio(ref decoder) => decoder.time_base(),
         }
     }
+
+    pub fn unused_options(&self) -> &[String] {
+        match *self {
+            Decoder::Video(ref decoder) => decoder.unused_options(),
+            Decoder::Audio(ref decoder) => decoder.unused_options(),
+        }
+    }
 }
 
 impl Decoder {
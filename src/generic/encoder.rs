@@ -71,6 +71,13 @@ impl Encoder {
         }
     }
 
+    pub fn unused_options(&self) -> &[String] {
+        match *self {
+            Encoder::Video(ref encoder) => encoder.unused_options(),
+            Encoder::Audio(ref encoder) => encoder.unused_options(),
+        }
+    }
+
     pub fn encode<'a, F>(&mut self, frame: F) -> Result<Packets> where
         F: Into<RefMutFrame<'a>>,
     {
@@ -2,6 +2,7 @@ use video;
 use audio;
 use errors::*;
 
+#[derive(Clone)]
 pub enum Frame {
     Video(video::Frame),
     Audio(audio::Frame),
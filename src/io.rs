@@ -1,17 +1,20 @@
 use std::fs::File;
 use std::net::TcpStream;
-use std::io::{Read,Write,Seek,SeekFrom};
+use std::io::{Read,Write,Seek,SeekFrom,Cursor};
+use std::sync::{Arc, Mutex};
 use std::{mem, slice};
 use std::os::raw::{self, c_void, c_int};
 use ffi;
 use util::PtrTakeExt;
+use errors::*;
 
 pub trait AVSeek: Sized + Send + 'static {
     /// Seek to `pos`. Returns `Some(new_pos)` on success
     /// and `None` on error.
     fn seek(&mut self, pos: SeekFrom) -> Option<u64>;
     /// The size of the data. It is optional to support this.
-    fn size(&self) -> Option<u64> {
+    /// Implementations may seek, but must restore the position.
+    fn size(&mut self) -> Option<u64> {
         None
     }
 }
@@ -55,10 +58,19 @@ impl IOContext {
         self.ptr
     }
 
-    pub fn from_reader<R: AVRead>(mut input: R) -> IOContext  {
+    /// Whether the underlying reader/writer supports seeking.
+    /// This is determined once on creation by probing `AVSeek::seek`.
+    pub fn is_seekable(&self) -> bool {
+        unsafe { (*self.ptr).seek.is_some() }
+    }
+
+    pub fn from_reader<R: AVRead>(mut input: R) -> Result<IOContext>  {
         unsafe {
             let buffer_size = R::buffer_size();
-            let buffer = ffi::av_malloc(buffer_size as usize * mem::size_of::<u8>()) as _;
+            let buffer = ffi::av_malloc(buffer_size as usize * mem::size_of::<u8>()) as *mut u8;
+            if buffer.is_null() {
+                bail!(ErrorKind::AllocFailed("AVIO buffer"));
+            }
             let write_flag = 0; // Make buffer read-only for ffmpeg
             let read_packet = Some(ffi_read_packet::<R> as _);
             let write_packet = None;
@@ -74,19 +86,26 @@ impl IOContext {
                 seek
             );
 
-            assert!(!avio_ctx.is_null(), "Could not allocate AVIO context");
+            if avio_ctx.is_null() {
+                io_dropper::<R>(this);
+                ffi::av_free(buffer as _);
+                bail!(ErrorKind::AllocFailed("AVIO context"));
+            }
 
-            IOContext {
+            Ok(IOContext {
                 ptr: avio_ctx,
                 io_dropper: io_dropper::<R>,
-            }
+            })
         }
     }
 
-    pub fn from_writer<W: AVWrite>(mut output: W) -> IOContext  {
+    pub fn from_writer<W: AVWrite>(mut output: W) -> Result<IOContext>  {
         unsafe {
             let buffer_size = W::buffer_size();
-            let buffer = ffi::av_malloc(buffer_size as usize * mem::size_of::<u8>()) as _;
+            let buffer = ffi::av_malloc(buffer_size as usize * mem::size_of::<u8>()) as *mut u8;
+            if buffer.is_null() {
+                bail!(ErrorKind::AllocFailed("AVIO buffer"));
+            }
             let write_flag = 1; // Make buffer writable for ffmpeg
             let read_packet = None;
             let write_packet = Some(ffi_write_packet::<W> as _);
@@ -102,12 +121,16 @@ impl IOContext {
                 seek
             );
 
-            assert!(!avio_ctx.is_null(), "Could not allocate AVIO context");
+            if avio_ctx.is_null() {
+                io_dropper::<W>(this);
+                ffi::av_free(buffer as _);
+                bail!(ErrorKind::AllocFailed("AVIO context"));
+            }
 
-            IOContext {
+            Ok(IOContext {
                 ptr: avio_ctx,
                 io_dropper: io_dropper::<W>,
-            }
+            })
         }
     }
 
@@ -143,35 +166,39 @@ unsafe fn io_dropper<T>(io: *mut c_void) {
 extern fn ffi_read_packet<R: AVRead>(this: *mut c_void, buf: *mut u8, buf_size: c_int) -> c_int {
     let this = unsafe { &mut *(this as *mut R) };
     let buf = unsafe { slice::from_raw_parts_mut(buf, buf_size as usize) };
-    let eof = -1;
-    this.read_packet(buf).map(|n_read| n_read as c_int).unwrap_or(eof)
+    match this.read_packet(buf) {
+        Some(0) | None => ffi::AVERROR_EOF,
+        Some(n_read) => n_read as c_int,
+    }
 }
 
 extern fn ffi_write_packet<W: AVWrite>(this: *mut c_void, buf: *mut u8, buf_size: c_int) -> c_int {
     let this = unsafe { &mut *(this as *mut W) };
     let buf = unsafe { slice::from_raw_parts(buf as *const _, buf_size as usize) };
-    let eof = -1;
-    this.write_packet(buf).map(|n_written| n_written as c_int).unwrap_or(eof)
+    match this.write_packet(buf) {
+        Some(0) | None => ffi::AVERROR_EIO,
+        Some(n_written) => n_written as c_int,
+    }
 }
 
 unsafe extern fn ffi_seek<S: AVSeek>(this: *mut c_void, offset: i64, whence: c_int) -> i64 {
     let this = &mut *(this as *mut S);
 
     if whence == ffi::AVSEEK_SIZE as c_int {
-        return this.size().and_then(u64_into_i64).unwrap_or(-1);
+        return this.size().and_then(u64_into_i64).unwrap_or(ffi::AVERROR_ENOSYS as i64);
     }
 
     let pos = match whence as u32 {
         ffi::SEEK_SET => match i64_into_u64(offset) {
             Some(offset) => SeekFrom::Start(offset),
-            None => return -1,
+            None => return ffi::AVERROR_EINVAL as i64,
         },
         ffi::SEEK_CUR => SeekFrom::Current(offset),
         ffi::SEEK_END => SeekFrom::End(offset),
-        _ => return -1,
+        _ => return ffi::AVERROR_EINVAL as i64,
     };
 
-    this.seek(pos).and_then(u64_into_i64).unwrap_or(-1)
+    this.seek(pos).and_then(u64_into_i64).unwrap_or(ffi::AVERROR_EIO as i64)
 }
 
 fn u64_into_i64(n: u64) -> Option<i64> {
@@ -194,7 +221,7 @@ impl AVSeek for File {
     fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
         Seek::seek(self, pos).ok()
     }
-    fn size(&self) -> Option<u64> {
+    fn size(&mut self) -> Option<u64> {
         self.metadata().map(|m| m.len()).ok()
     }
 }
@@ -215,7 +242,7 @@ impl AVSeek for TcpStream {
     fn seek(&mut self, _pos: SeekFrom) -> Option<u64> {
         None
     }
-    fn size(&self) -> Option<u64> {
+    fn size(&mut self) -> Option<u64> {
         None
     }
 }
@@ -231,3 +258,253 @@ impl AVWrite for TcpStream {
         self.write(buf).ok()
     }
 }
+
+impl AVSeek for Cursor<Vec<u8>> {
+    fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
+        Seek::seek(self, pos).ok()
+    }
+    fn size(&mut self) -> Option<u64> {
+        Some(self.get_ref().len() as u64)
+    }
+}
+
+impl AVRead for Cursor<Vec<u8>> {
+    fn read_packet(&mut self, buf: &mut [u8]) -> Option<usize> {
+        self.read(buf).ok()
+    }
+}
+
+impl AVWrite for Cursor<Vec<u8>> {
+    fn write_packet(&mut self, buf: &[u8]) -> Option<usize> {
+        self.write(buf).ok()
+    }
+}
+
+impl AVSeek for Cursor<&'static [u8]> {
+    fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
+        Seek::seek(self, pos).ok()
+    }
+    fn size(&mut self) -> Option<u64> {
+        Some(self.get_ref().len() as u64)
+    }
+}
+
+impl AVRead for Cursor<&'static [u8]> {
+    fn read_packet(&mut self, buf: &mut [u8]) -> Option<usize> {
+        self.read(buf).ok()
+    }
+}
+
+impl AVSeek for Cursor<Arc<[u8]>> {
+    fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
+        Seek::seek(self, pos).ok()
+    }
+    fn size(&mut self) -> Option<u64> {
+        Some(self.get_ref().len() as u64)
+    }
+}
+
+impl AVRead for Cursor<Arc<[u8]>> {
+    fn read_packet(&mut self, buf: &mut [u8]) -> Option<usize> {
+        self.read(buf).ok()
+    }
+}
+
+/// A growable in-memory buffer that can be used as muxer output.
+///
+/// The buffer is shared between its clones, so the written data
+/// can be taken out after the muxer has been closed:
+///
+/// ```ignore
+/// let buffer = MemoryBuffer::new();
+/// let muxer = Muxer::new(format, buffer.clone())?;
+/// // ...
+/// muxer.close()?;
+/// let data = buffer.take();
+/// ```
+#[derive(Clone,Default)]
+pub struct MemoryBuffer {
+    inner: Arc<Mutex<Cursor<Vec<u8>>>>,
+}
+
+impl MemoryBuffer {
+    pub fn new() -> Self {
+        Self::from_vec(Vec::new())
+    }
+
+    /// Create a buffer containing `data`, positioned at the start.
+    pub fn from_vec(data: Vec<u8>) -> Self {
+        MemoryBuffer {
+            inner: Arc::new(Mutex::new(Cursor::new(data))),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().get_ref().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copy the data written so far.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.inner.lock().unwrap().get_ref().clone()
+    }
+
+    /// Take the data out of the buffer, leaving it empty.
+    pub fn take(&self) -> Vec<u8> {
+        let mut inner = self.inner.lock().unwrap();
+        let data = mem::replace(inner.get_mut(), Vec::new());
+        inner.set_position(0);
+        data
+    }
+}
+
+impl AVSeek for MemoryBuffer {
+    fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
+        Seek::seek(&mut *self.inner.lock().unwrap(), pos).ok()
+    }
+    fn size(&mut self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+impl AVRead for MemoryBuffer {
+    fn read_packet(&mut self, buf: &mut [u8]) -> Option<usize> {
+        self.inner.lock().unwrap().read(buf).ok()
+    }
+}
+
+impl AVWrite for MemoryBuffer {
+    fn write_packet(&mut self, buf: &[u8]) -> Option<usize> {
+        self.inner.lock().unwrap().write_all(buf).ok().map(|_| buf.len())
+    }
+}
+
+/// Adapter to use any `Read + Seek` type as custom input source.
+pub struct ReadAdapter<R>(R);
+
+impl<R: Read + Seek + Send + 'static> ReadAdapter<R> {
+    pub fn new(reader: R) -> Self {
+        ReadAdapter(reader)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+impl<R: Read + Seek + Send + 'static> AVSeek for ReadAdapter<R> {
+    fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
+        self.0.seek(pos).ok()
+    }
+    fn size(&mut self) -> Option<u64> {
+        seek_size(&mut self.0)
+    }
+}
+
+impl<R: Read + Seek + Send + 'static> AVRead for ReadAdapter<R> {
+    fn read_packet(&mut self, buf: &mut [u8]) -> Option<usize> {
+        self.0.read(buf).ok()
+    }
+}
+
+/// Adapter to use any `Write + Seek` type as custom output source.
+pub struct WriteAdapter<W>(W);
+
+impl<W: Write + Seek + Send + 'static> WriteAdapter<W> {
+    pub fn new(writer: W) -> Self {
+        WriteAdapter(writer)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: Write + Seek + Send + 'static> AVSeek for WriteAdapter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> Option<u64> {
+        self.0.seek(pos).ok()
+    }
+    fn size(&mut self) -> Option<u64> {
+        seek_size(&mut self.0)
+    }
+}
+
+impl<W: Write + Seek + Send + 'static> AVWrite for WriteAdapter<W> {
+    fn write_packet(&mut self, buf: &[u8]) -> Option<usize> {
+        // avio treats short writes as success, so write everything or fail
+        self.0.write_all(buf).ok().map(|_| buf.len())
+    }
+}
+
+/// Determine the size by seeking to the end and back.
+fn seek_size<S: Seek>(stream: &mut S) -> Option<u64> {
+    let pos = match stream.seek(SeekFrom::Current(0)) {
+        Ok(pos) => pos,
+        Err(_) => return None,
+    };
+    let size = stream.seek(SeekFrom::End(0)).ok();
+
+    match stream.seek(SeekFrom::Start(pos)) {
+        Ok(_) => size,
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, SeekFrom};
+    use super::{AVSeek, AVRead, AVWrite, MemoryBuffer, ReadAdapter};
+
+    #[test]
+    fn memory_buffer_shares_data() {
+        let buffer = MemoryBuffer::new();
+        let mut writer = buffer.clone();
+
+        assert_eq!(writer.write_packet(b"hello"), Some(5));
+        assert_eq!(AVSeek::seek(&mut writer, SeekFrom::Start(1)), Some(1));
+        assert_eq!(writer.write_packet(b"a"), Some(1));
+        assert_eq!(writer.size(), Some(5));
+
+        assert_eq!(buffer.take(), b"hallo");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn read_adapter_seeks() {
+        let mut reader = ReadAdapter::new(Cursor::new(vec![1, 2, 3, 4]));
+        let mut buf = [0; 2];
+
+        assert_eq!(AVSeek::seek(&mut reader, SeekFrom::End(-2)), Some(2));
+        assert_eq!(reader.read_packet(&mut buf), Some(2));
+        assert_eq!(buf, [3, 4]);
+        assert_eq!(reader.read_packet(&mut buf), Some(0));
+    }
+
+    #[test]
+    fn read_adapter_size() {
+        let mut reader = ReadAdapter::new(Cursor::new(vec![1, 2, 3, 4]));
+
+        assert_eq!(AVSeek::seek(&mut reader, SeekFrom::Start(1)), Some(1));
+        assert_eq!(reader.size(), Some(4));
+        assert_eq!(AVSeek::seek(&mut reader, SeekFrom::Current(0)), Some(1));
+    }
+}
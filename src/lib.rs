@@ -8,8 +8,11 @@ extern crate bitflags;
 #[macro_use]
 extern crate error_chain;
 extern crate smallvec;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log as log_crate;
 use std::ffi::CStr;
-use std::sync::{Once, ONCE_INIT};
+use std::sync::{Arc, Once, ONCE_INIT};
 use util::AsCStr;
 
 #[macro_use]
@@ -20,9 +23,11 @@ pub mod format;
 pub mod video;
 pub mod audio;
 pub mod generic;
+pub mod filter;
 
 pub mod io;
 pub mod codec;
+mod logging;
 
 pub mod errors;
 pub use self::errors::*;
@@ -40,6 +45,8 @@ impl LibAV {
             INIT.call_once(|| {
                 // Init avformat
                 ffi::av_register_all();
+                // Init avfilter
+                ffi::avfilter_register_all();
             });
 
             LibAV(())
@@ -52,6 +59,28 @@ impl LibAV {
         }
     }
 
+    /// Route libav log messages to `callback` instead of stderr.
+    ///
+    /// The callback receives the level, the name of the logging component
+    /// (e.g. `h264` or `mp4`) if known, and the formatted message.
+    /// Messages above the level set by `set_log_level` are discarded.
+    pub fn set_log_callback<F>(&self, callback: F) where
+        F: Fn(LogLevel, Option<&str>, &str) + Send + Sync + 'static,
+    {
+        logging::set_callback(Some(Arc::new(callback)));
+    }
+
+    /// Restore the default libav logging to stderr.
+    pub fn reset_log_callback(&self) {
+        logging::set_callback(None);
+    }
+
+    /// Route libav log messages to the `log` crate using the target `av`.
+    #[cfg(feature = "log")]
+    pub fn set_log_crate_callback(&self) {
+        self.set_log_callback(logging::forward_to_log_crate);
+    }
+
     pub fn version(&self) -> &'static CStr {
         unsafe {
             ffi::av_version_info().as_cstr().unwrap()
@@ -66,6 +95,7 @@ impl LibAV {
 }
 
 #[repr(i32)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
 pub enum LogLevel {
     /// Print no output.
     Quiet = ffi::AV_LOG_QUIET as i32,
@@ -92,3 +122,21 @@ pub enum LogLevel {
     /// Extremely verbose debugging, useful for libav* development.
     Trace = ffi::AV_LOG_TRACE as i32,
 }
+
+impl LogLevel {
+    /// Get the level a raw libav log level belongs to.
+    pub fn from_raw(level: i32) -> LogLevel {
+        // The upper bits may contain color information
+        let level = if level >= 0 { level & 0xFF } else { level };
+
+        if      level <  ffi::AV_LOG_PANIC   as i32 { LogLevel::Quiet }
+        else if level <  ffi::AV_LOG_FATAL   as i32 { LogLevel::Panic }
+        else if level <  ffi::AV_LOG_ERROR   as i32 { LogLevel::Fatal }
+        else if level <  ffi::AV_LOG_WARNING as i32 { LogLevel::Error }
+        else if level <  ffi::AV_LOG_INFO    as i32 { LogLevel::Warning }
+        else if level <  ffi::AV_LOG_VERBOSE as i32 { LogLevel::Info }
+        else if level <  ffi::AV_LOG_DEBUG   as i32 { LogLevel::Verbose }
+        else if level <  ffi::AV_LOG_TRACE   as i32 { LogLevel::Debug }
+        else                                        { LogLevel::Trace }
+    }
+}
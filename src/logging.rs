@@ -0,0 +1,115 @@
+use std::sync::{Arc, RwLock};
+use std::ffi::CStr;
+use std::panic::{self, AssertUnwindSafe};
+use std::os::raw::{c_char, c_int, c_void};
+use ffi::{self, AVClass};
+use LogLevel;
+
+pub type LogCallback = Arc<Fn(LogLevel, Option<&str>, &str) + Send + Sync>;
+
+/// Extracts the platform-specific `va_list` parameter type from a log callback signature.
+trait LogCallbackSignature {
+    type VaList;
+}
+
+impl<V> LogCallbackSignature for Option<unsafe extern "C" fn(*mut c_void, c_int, *const c_char, V)> {
+    type VaList = V;
+}
+
+type VaList = <ffi::RUST_AV_LOG_CALLBACK as LogCallbackSignature>::VaList;
+
+lazy_static! {
+    static ref LOG_CALLBACK: RwLock<Option<LogCallback>> = RwLock::new(None);
+}
+
+pub fn set_callback(callback: Option<LogCallback>) {
+    unsafe {
+        let has_callback = callback.is_some();
+        *LOG_CALLBACK.write().unwrap() = callback;
+
+        if has_callback {
+            ffi::av_log_set_callback(Some(ffi_log_callback));
+        } else {
+            ffi::av_log_set_callback(Some(ffi::av_log_default_callback));
+        }
+    }
+}
+
+unsafe extern "C" fn ffi_log_callback(avcl: *mut c_void, level: c_int, fmt: *const c_char, vl: VaList) {
+    // The upper bits may contain color information
+    let raw_level = if level >= 0 { level & 0xFF } else { level };
+    if raw_level > ffi::av_log_get_level() {
+        return;
+    }
+
+    // Format the message without the "[class @ 0x...]" prefix
+    let mut line = [0 as c_char; 1024];
+    let mut print_prefix = 0;
+    ffi::av_log_format_line(avcl, level, fmt, vl, line.as_mut_ptr(), line.len() as c_int, &mut print_prefix);
+
+    let message = CStr::from_ptr(line.as_ptr()).to_string_lossy();
+    let message = message.trim_right_matches('\n');
+    if message.is_empty() {
+        return;
+    }
+
+    let class_name = class_name(avcl);
+    let class_name = class_name.as_ref().map(|name| &**name);
+
+    // Release the lock before calling back, which may replace the callback
+    let callback = match LOG_CALLBACK.read() {
+        Ok(callback) => callback.clone(),
+        Err(_) => return,
+    };
+
+    if let Some(callback) = callback {
+        // Unwinding into libav is undefined behaviour, so the message is dropped instead
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            callback(LogLevel::from_raw(level), class_name, message);
+        }));
+    }
+}
+
+/// The item name of a libav context (e.g. `h264` or `mp4`),
+/// which starts with a pointer to its `AVClass`.
+unsafe fn class_name(avcl: *mut c_void) -> Option<String> {
+    if avcl.is_null() {
+        return None;
+    }
+
+    let class = *(avcl as *const *const AVClass);
+    if class.is_null() {
+        return None;
+    }
+
+    let name = match (*class).item_name {
+        Some(item_name) => item_name(avcl),
+        None => (*class).class_name,
+    };
+
+    if name.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(name).to_string_lossy().into_owned())
+    }
+}
+
+/// Forward a log message to the `log` crate.
+#[cfg(feature = "log")]
+pub fn forward_to_log_crate(level: LogLevel, class_name: Option<&str>, message: &str) {
+    use log_crate::LogLevel as Level;
+
+    let level = match level {
+        LogLevel::Quiet => return,
+        LogLevel::Panic | LogLevel::Fatal | LogLevel::Error => Level::Error,
+        LogLevel::Warning => Level::Warn,
+        LogLevel::Info => Level::Info,
+        LogLevel::Verbose => Level::Debug,
+        LogLevel::Debug | LogLevel::Trace => Level::Trace,
+    };
+
+    match class_name {
+        Some(class_name) => log!(target: "av", level, "[{}] {}", class_name, message),
+        None => log!(target: "av", level, "{}", message),
+    }
+}
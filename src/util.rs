@@ -3,6 +3,8 @@ use std::ptr;
 use std::ffi::CStr;
 use std::os::raw::c_char;
 use std::ops;
+use ffi::AVRational;
+use common::Timebase;
 
 pub trait PtrTakeExt {
     fn take(&mut self) -> Self;
@@ -58,3 +60,62 @@ impl<'a, T: 'a> ops::DerefMut for OwnedOrRefMut<'a, T> {
         }
     } 
 }
+
+/// Convert a rational to a `Timebase`.
+/// Returns `None` for the `0/1` and `0/0` values libav uses for "unknown".
+pub fn nonzero_rational(rational: AVRational) -> Option<Timebase> {
+    if rational.num == 0 || rational.den == 0 {
+        None
+    } else {
+        Some(rational.into())
+    }
+}
+
+/// Define a Rust enum mirroring the commonly used values of a bindgen enum.
+/// Values without a dedicated variant are kept as `Other(raw)`.
+macro_rules! raw_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident($raw:ident) {
+            $($(#[$variant_attr:meta])* $variant:ident = $raw_variant:ident,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+        pub enum $name {
+            $($(#[$variant_attr])* $variant,)*
+            /// A value without a dedicated variant.
+            /// Use `from_raw` to create values, so that known values
+            /// don't end up as `Other`.
+            Other(::ffi::$raw),
+        }
+
+        impl $name {
+            pub fn from_raw(raw: ::ffi::$raw) -> Self {
+                match raw {
+                    $(::ffi::$raw::$raw_variant => $name::$variant,)*
+                    raw => $name::Other(raw),
+                }
+            }
+
+            pub fn as_raw(self) -> ::ffi::$raw {
+                match self {
+                    $($name::$variant => ::ffi::$raw::$raw_variant,)*
+                    $name::Other(raw) => raw,
+                }
+            }
+        }
+
+        impl From<::ffi::$raw> for $name {
+            fn from(raw: ::ffi::$raw) -> Self {
+                $name::from_raw(raw)
+            }
+        }
+
+        impl From<$name> for ::ffi::$raw {
+            fn from(value: $name) -> Self {
+                value.as_raw()
+            }
+        }
+    }
+}
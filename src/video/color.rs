@@ -0,0 +1,83 @@
+raw_enum! {
+    /// The range of the luma/color values.
+    pub enum ColorRange(AVColorRange) {
+        Unspecified = AVCOL_RANGE_UNSPECIFIED,
+        /// Limited range, e.g. 16-235 for 8 bit luma ("TV range").
+        Mpeg        = AVCOL_RANGE_MPEG,
+        /// Full range, e.g. 0-255 for 8 bit luma ("PC range").
+        Jpeg        = AVCOL_RANGE_JPEG,
+    }
+}
+
+raw_enum! {
+    /// The chromaticity coordinates of the source primaries.
+    pub enum ColorPrimaries(AVColorPrimaries) {
+        Unspecified = AVCOL_PRI_UNSPECIFIED,
+        Bt709       = AVCOL_PRI_BT709,
+        Bt470M      = AVCOL_PRI_BT470M,
+        Bt470Bg     = AVCOL_PRI_BT470BG,
+        Smpte170M   = AVCOL_PRI_SMPTE170M,
+        Smpte240M   = AVCOL_PRI_SMPTE240M,
+        Film        = AVCOL_PRI_FILM,
+        Bt2020      = AVCOL_PRI_BT2020,
+        /// SMPTE ST 431-2 (DCI-P3).
+        Smpte431    = AVCOL_PRI_SMPTE431,
+        /// SMPTE ST 432-1 (Display P3).
+        Smpte432    = AVCOL_PRI_SMPTE432,
+    }
+}
+
+raw_enum! {
+    /// The transfer characteristic (e.g. gamma curve) of the color values.
+    pub enum ColorTransferCharacteristic(AVColorTransferCharacteristic) {
+        Unspecified  = AVCOL_TRC_UNSPECIFIED,
+        Bt709        = AVCOL_TRC_BT709,
+        Gamma22      = AVCOL_TRC_GAMMA22,
+        Gamma28      = AVCOL_TRC_GAMMA28,
+        Smpte170M    = AVCOL_TRC_SMPTE170M,
+        Smpte240M    = AVCOL_TRC_SMPTE240M,
+        Linear       = AVCOL_TRC_LINEAR,
+        Log          = AVCOL_TRC_LOG,
+        LogSqrt      = AVCOL_TRC_LOG_SQRT,
+        Iec61966_2_4 = AVCOL_TRC_IEC61966_2_4,
+        Bt1361Ecg    = AVCOL_TRC_BT1361_ECG,
+        Iec61966_2_1 = AVCOL_TRC_IEC61966_2_1,
+        Bt2020_10    = AVCOL_TRC_BT2020_10,
+        Bt2020_12    = AVCOL_TRC_BT2020_12,
+        /// SMPTE ST 2084 (PQ), used for HDR10.
+        Smpte2084    = AVCOL_TRC_SMPTE2084,
+        /// ARIB STD-B67 (HLG).
+        AribStdB67   = AVCOL_TRC_ARIB_STD_B67,
+    }
+}
+
+raw_enum! {
+    /// The matrix used to derive luma and chroma from RGB.
+    pub enum ColorSpace(AVColorSpace) {
+        Rgb         = AVCOL_SPC_RGB,
+        Bt709       = AVCOL_SPC_BT709,
+        Unspecified = AVCOL_SPC_UNSPECIFIED,
+        Fcc         = AVCOL_SPC_FCC,
+        Bt470Bg     = AVCOL_SPC_BT470BG,
+        Smpte170M   = AVCOL_SPC_SMPTE170M,
+        Smpte240M   = AVCOL_SPC_SMPTE240M,
+        Bt2020Ncl   = AVCOL_SPC_BT2020_NCL,
+        Bt2020Cl    = AVCOL_SPC_BT2020_CL,
+    }
+}
+
+raw_enum! {
+    /// The location of the chroma samples relative to the luma samples.
+    pub enum ChromaLocation(AVChromaLocation) {
+        Unspecified = AVCHROMA_LOC_UNSPECIFIED,
+        /// MPEG-2/4 4:2:0, H.264 default for 4:2:0
+        Left        = AVCHROMA_LOC_LEFT,
+        /// MPEG-1 4:2:0, JPEG 4:2:0, H.263 4:2:0
+        Center      = AVCHROMA_LOC_CENTER,
+        /// ITU-R 601, SMPTE 274M 296M S314M(DV 4:1:1), mpeg2 4:2:2
+        TopLeft     = AVCHROMA_LOC_TOPLEFT,
+        Top         = AVCHROMA_LOC_TOP,
+        BottomLeft  = AVCHROMA_LOC_BOTTOMLEFT,
+        Bottom      = AVCHROMA_LOC_BOTTOM,
+    }
+}
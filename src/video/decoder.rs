@@ -4,12 +4,14 @@ use ffi::AVCodecContext;
 use codec::{Codec,MediaType};
 use common::codec_parameters::CodecParameters;
 use common::stream::Stream;
-use common::{Packet, Timebase};
-use super::Frame;
+use common::{Packet, Timebase, Options};
+use common::options;
+use super::{Frame, PixelFormat};
 use errors::*;
 
 pub struct Decoder {
     ptr: *mut AVCodecContext,
+    unused_options: Vec<String>,
 }
 
 unsafe impl Send for Decoder{}
@@ -18,6 +20,12 @@ unsafe impl Sync for Decoder{}
 impl Decoder {
     // TODO: Share code between audio/video
     pub fn from_codec_parameters<'fmt_ctx>(codec_parameters: CodecParameters<'fmt_ctx>) -> Result<Self> {
+        Self::from_codec_parameters_with_options(codec_parameters, &Options::new())
+    }
+
+    /// Open the decoder using the given codec options (e.g. `threads`).
+    /// Options not consumed by the decoder are reported by `unused_options`.
+    pub fn from_codec_parameters_with_options<'fmt_ctx>(codec_parameters: CodecParameters<'fmt_ctx>, options: &Options) -> Result<Self> {
         unsafe {
             let codec_id = codec_parameters.codec_id();
 
@@ -30,7 +38,7 @@ impl Decoder {
             // Try to allocate the decoder
             let mut codec_context = ffi::avcodec_alloc_context3(codec.as_ptr());
             if codec_context.is_null() {
-                bail!("Could not allocate video decoder");
+                bail!(ErrorKind::AllocFailed("video decoder"));
             }
 
             // Copy codec parameters to codec_parameters
@@ -38,22 +46,31 @@ impl Decoder {
                 let res = ffi::avcodec_parameters_to_context(codec_context, codec_parameters.as_ptr());
                 if res < 0 {
                     ffi::avcodec_free_context(&mut codec_context);
-                    bail!(ErrorKind::CopyCodecParameters);
+                    bail!(ErrorKind::av(res, "Could not copy codec parameters"));
                 }
             }
 
             // Try to open the decoder
-            {
-                let options = ptr::null_mut();
-                let res = ffi::avcodec_open2(codec_context, codec.as_ptr(), options);
+            let unused_options = {
+                let mut options = match options.to_dict() {
+                    Ok(options) => options,
+                    Err(err) => {
+                        ffi::avcodec_free_context(&mut codec_context);
+                        return Err(err);
+                    },
+                };
+                let res = ffi::avcodec_open2(codec_context, codec.as_ptr(), &mut options);
+                let unused_options = options::take_unused(&mut options);
                 if res < 0 {
                     ffi::avcodec_free_context(&mut codec_context);
-                    bail!(ErrorKind::OpenDecoder("video"));
+                    bail!(ErrorKind::av(res, "Could not open video decoder"));
                 }
-            }
+                unused_options
+            };
 
             Ok(Decoder {
                 ptr: codec_context,
+                unused_options: unused_options,
             })
         }
     }
@@ -62,6 +79,15 @@ impl Decoder {
         Self::from_codec_parameters(stream.codec_parameters())
     }
 
+    pub fn from_stream_with_options(stream: &Stream, options: &Options) -> Result<Self> {
+        Self::from_codec_parameters_with_options(stream.codec_parameters(), options)
+    }
+
+    /// Codec options that were not consumed when opening the decoder.
+    pub fn unused_options(&self) -> &[String] {
+        &self.unused_options
+    }
+
     pub fn codec(&self) -> Codec {
         unsafe {
             Codec::from_ptr(self.as_ref().codec)
@@ -72,8 +98,8 @@ impl Decoder {
         self.as_ref().time_base.into()
     }
 
-    pub fn pixel_format(&self) -> ffi::AVPixelFormat {
-        self.as_ref().pix_fmt
+    pub fn pixel_format(&self) -> PixelFormat {
+        PixelFormat::from_raw(self.as_ref().pix_fmt)
     }
 
     pub fn decode<'decoder>(&'decoder mut self, mut packet: Packet) -> Result<Frames<'decoder>> {
@@ -82,10 +108,8 @@ impl Decoder {
         unsafe {
             let res = ffi::avcodec_send_packet(self.as_mut_ptr(), packet.as_mut_ptr());
             if res < 0 {
-                match res {
-                    ffi::AVERROR_EAGAIN => bail!("EAGAIN in Decoder::decode. This is not supposed to happen :("),
-                    _ => bail!(format!("Failed to decode packet: 0x{:X}", res))
-                }
+                // EAGAIN is not supposed to happen, because all frames are received after each packet
+                bail!(ErrorKind::av(res, "Failed to decode packet"))
             }
 
             Ok(Frames::from_decoder(self))
@@ -99,7 +123,7 @@ impl Decoder {
             let res = ffi::avcodec_send_packet(self.as_mut_ptr(), ptr::null_mut());
 
             if res < 0 && res != ffi::AVERROR_EAGAIN {
-                bail!(format!("Failed to flush decoder: 0x{:X}", res))
+                bail!(ErrorKind::av(res, "Failed to flush decoder"))
             }
 
             Ok(Frames::from_decoder(self))
@@ -139,7 +163,7 @@ impl<'decoder> Iterator for Frames<'decoder> {
 
                 match res {
                     ffi::AVERROR_EAGAIN | ffi::AVERROR_EOF => return None,
-                    _ => return Some(Err(format!("Failed to receive frame: 0x{:X}", res).into())),
+                    _ => return Some(Err(ErrorKind::av(res, "Failed to receive frame").into())),
                 }
             }
 
@@ -1,23 +1,41 @@
 use std::ptr;
 use std::os::raw::c_int;
+use std::ffi::{CStr, CString};
 use LibAV;
 use codec::{
     Codec,
     MediaType,
+    ThreadType,
+    THREAD_FRAME,
+    THREAD_SLICE,
+    CAP_FRAME_THREADS,
+    CAP_SLICE_THREADS,
 };
 use ffi;
 use ffi::{
     AVCodecContext,
-    AVPixelFormat,
     avcodec_alloc_context3,
     avcodec_free_context,
+    AV_CODEC_FLAG_QSCALE,
+    AV_CODEC_PROP_INTRA_ONLY,
+    AV_CODEC_PROP_REORDER,
+    FF_QP2LAMBDA,
 };
 use format::OutputFormat;
 use generic::RefMutFrame;
-use common::{self, Packet, Timebase};
+use common::{self, Packet, Timebase, Options};
 use errors::*;
 use util::OwnedOrRefMut;
-use super::{Frame, Scaler};
+use super::{
+    Frame,
+    Scaler,
+    PixelFormat,
+    ColorRange,
+    ColorPrimaries,
+    ColorTransferCharacteristic,
+    ColorSpace,
+    ChromaLocation,
+};
 
 // TODO: Add align field to encoder
 const ALIGN: usize = 32;
@@ -26,6 +44,7 @@ pub struct Encoder {
     ptr: *mut AVCodecContext,
     scaler: Scaler,
     tmp_frame: Option<Frame>,
+    unused_options: Vec<String>,
 }
 
 unsafe impl Send for Encoder {}
@@ -36,8 +55,8 @@ impl Encoder {
         EncoderBuilder::from_codec(codec)
     }
 
-    pub fn pixel_format(&self) -> ffi::AVPixelFormat {
-        self.as_ref().pix_fmt
+    pub fn pixel_format(&self) -> PixelFormat {
+        PixelFormat::from_raw(self.as_ref().pix_fmt)
     }
 
     pub fn width(&self) -> usize {
@@ -57,6 +76,11 @@ impl Encoder {
     pub fn time_base(&self) -> Timebase {
         self.as_ref().time_base.into()
     }
+
+    /// Codec options that were not consumed when opening the encoder.
+    pub fn unused_options(&self) -> &[String] {
+        &self.unused_options
+    }
 }
 
 impl Encoder {
@@ -77,7 +101,7 @@ impl Encoder {
                 scaler.scale_frame(&mut frame, tmp_frame)?;
 
                 // Copy frame data
-                tmp_frame.set_pts(frame.pts());
+                copy_frame_props(frame, tmp_frame)?;
                 frame = tmp_frame;
             }        
 
@@ -88,7 +112,7 @@ impl Encoder {
 
                 let res = ffi::avcodec_send_frame(self.ptr, frame.as_mut_ptr());
                 if res < 0 {
-                    bail!("Could not encode frame: 0x{:X}", res)
+                    bail!(ErrorKind::av(res, "Could not encode frame"))
                 }
             }
         }
@@ -101,7 +125,7 @@ impl Encoder {
             // Flush encoder
             let res = ffi::avcodec_send_frame(self.ptr, ptr::null_mut());
             if res < 0 {
-                bail!("Could not flush encoder: 0x{:X}", res)
+                bail!(ErrorKind::av(res, "Could not flush encoder"))
             }
 
             Ok(Packets::from_encoder(self))
@@ -116,6 +140,22 @@ impl Encoder {
     }
 }
 
+/// Copy the properties and side data of `src` that don't depend on the pixel format to the scaled frame `dst`.
+/// The color range and space of `dst` are kept, since they follow from its pixel format.
+fn copy_frame_props(src: &Frame, dst: &mut Frame) -> Result<()> {
+    dst.set_pts(src.pts());
+    dst.set_pkt_duration(src.pkt_duration());
+    dst.set_key_frame(src.is_key_frame());
+    dst.set_picture_type(src.picture_type());
+    dst.set_sample_aspect_ratio(src.sample_aspect_ratio());
+    dst.set_interlaced(src.is_interlaced());
+    dst.set_top_field_first(src.is_top_field_first());
+    dst.set_repeat_pict(src.repeat_pict());
+    dst.set_color_primaries(src.color_primaries());
+    dst.set_color_trc(src.color_trc());
+    dst.side_data_mut().copy_from(src.side_data())
+}
+
 impl Encoder {
     pub fn as_mut(&mut self) -> &mut AVCodecContext { unsafe { &mut *self.ptr } }
     pub fn as_ptr(&self) -> *const AVCodecContext { self.ptr }
@@ -138,14 +178,32 @@ impl Drop for Encoder {
     }
 }
 
-/// TODO: Check for invalid value ranges
 pub struct EncoderBuilder {
     codec: Codec,
-    pixel_format: Option<AVPixelFormat>,
+    pixel_format: Option<PixelFormat>,
     width: Option<c_int>,
     height: Option<c_int>,
     time_base: Option<Timebase>,
     bitrate: Option<i64>,
+    max_rate: Option<i64>,
+    min_rate: Option<i64>,
+    buffer_size: Option<usize>,
+    gop_size: Option<usize>,
+    max_b_frames: Option<usize>,
+    qmin: Option<u32>,
+    qmax: Option<u32>,
+    global_quality: Option<u32>,
+    crf: Option<f64>,
+    profile: Option<c_int>,
+    level: Option<c_int>,
+    thread_count: Option<usize>,
+    thread_type: Option<ThreadType>,
+    color_range: Option<ColorRange>,
+    color_primaries: Option<ColorPrimaries>,
+    color_trc: Option<ColorTransferCharacteristic>,
+    colorspace: Option<ColorSpace>,
+    chroma_location: Option<ChromaLocation>,
+    options: Options,
 }
 
 impl EncoderBuilder {
@@ -160,6 +218,25 @@ impl EncoderBuilder {
             height: None,
             time_base: None,
             bitrate: None,
+            max_rate: None,
+            min_rate: None,
+            buffer_size: None,
+            gop_size: None,
+            max_b_frames: None,
+            qmin: None,
+            qmax: None,
+            global_quality: None,
+            crf: None,
+            profile: None,
+            level: None,
+            thread_count: None,
+            thread_type: None,
+            color_range: None,
+            color_primaries: None,
+            color_trc: None,
+            colorspace: None,
+            chroma_location: None,
+            options: Options::new(),
         })
     }
 
@@ -173,7 +250,7 @@ impl EncoderBuilder {
         self.height = Some(height as i32); self
     }
 
-    pub fn pixel_format(&mut self, pixel_format: AVPixelFormat) -> &mut Self {
+    pub fn pixel_format(&mut self, pixel_format: PixelFormat) -> &mut Self {
         self.pixel_format = Some(pixel_format); self
     }
 
@@ -181,6 +258,112 @@ impl EncoderBuilder {
         self.time_base = Some(time_base.into()); self
     }
 
+    /// Average bitrate in bits per second.
+    pub fn bitrate(&mut self, bitrate: i64) -> &mut Self {
+        self.bitrate = Some(bitrate); self
+    }
+
+    /// Maximum bitrate in bits per second. Requires `buffer_size`.
+    pub fn max_rate(&mut self, max_rate: i64) -> &mut Self {
+        self.max_rate = Some(max_rate); self
+    }
+
+    /// Minimum bitrate in bits per second.
+    pub fn min_rate(&mut self, min_rate: i64) -> &mut Self {
+        self.min_rate = Some(min_rate); self
+    }
+
+    /// Rate control (VBV) buffer size in bits.
+    pub fn buffer_size(&mut self, buffer_size: usize) -> &mut Self {
+        self.buffer_size = Some(buffer_size); self
+    }
+
+    /// Maximum number of frames between keyframes.
+    pub fn gop_size(&mut self, gop_size: usize) -> &mut Self {
+        self.gop_size = Some(gop_size); self
+    }
+
+    /// Maximum number of consecutive B-frames.
+    pub fn max_b_frames(&mut self, max_b_frames: usize) -> &mut Self {
+        self.max_b_frames = Some(max_b_frames); self
+    }
+
+    /// Minimum quantizer. The valid range depends on the codec
+    /// (e.g. 1-31 for MPEG-4, 0-63 for VP8/VP9) and is checked when opening the encoder.
+    pub fn qmin(&mut self, qmin: u32) -> &mut Self {
+        self.qmin = Some(qmin); self
+    }
+
+    /// Maximum quantizer. The valid range depends on the codec
+    /// (e.g. 1-31 for MPEG-4, 0-63 for VP8/VP9) and is checked when opening the encoder.
+    pub fn qmax(&mut self, qmax: u32) -> &mut Self {
+        self.qmax = Some(qmax); self
+    }
+
+    /// Encode with a constant quantizer (`-q:v` / `-qscale:v`) instead of a target bitrate.
+    pub fn global_quality(&mut self, quality: u32) -> &mut Self {
+        self.global_quality = Some(quality); self
+    }
+
+    /// Constant rate factor, for codecs that provide a private `crf` option (e.g. libx264).
+    pub fn crf(&mut self, crf: f64) -> &mut Self {
+        self.crf = Some(crf); self
+    }
+
+    /// Codec profile, e.g. `ffi::FF_PROFILE_H264_HIGH`.
+    pub fn profile(&mut self, profile: c_int) -> &mut Self {
+        self.profile = Some(profile); self
+    }
+
+    /// Codec level, e.g. `41` for H.264 level 4.1.
+    pub fn level(&mut self, level: c_int) -> &mut Self {
+        self.level = Some(level); self
+    }
+
+    /// Number of encoding threads. `0` lets the codec decide.
+    pub fn thread_count(&mut self, thread_count: usize) -> &mut Self {
+        self.thread_count = Some(thread_count); self
+    }
+
+    pub fn thread_type(&mut self, thread_type: ThreadType) -> &mut Self {
+        self.thread_type = Some(thread_type); self
+    }
+
+    /// The color properties are written into the stream headers by most encoders.
+    /// They should match the properties of the encoded frames.
+    pub fn color_range(&mut self, color_range: ColorRange) -> &mut Self {
+        self.color_range = Some(color_range); self
+    }
+
+    pub fn color_primaries(&mut self, color_primaries: ColorPrimaries) -> &mut Self {
+        self.color_primaries = Some(color_primaries); self
+    }
+
+    pub fn color_trc(&mut self, color_trc: ColorTransferCharacteristic) -> &mut Self {
+        self.color_trc = Some(color_trc); self
+    }
+
+    pub fn colorspace(&mut self, colorspace: ColorSpace) -> &mut Self {
+        self.colorspace = Some(colorspace); self
+    }
+
+    pub fn chroma_location(&mut self, chroma_location: ChromaLocation) -> &mut Self {
+        self.chroma_location = Some(chroma_location); self
+    }
+
+    /// Set a codec option, e.g. `option("preset", "veryfast")`.
+    /// Fails if `key` or `value` contain a nul byte.
+    pub fn option(&mut self, key: &str, value: &str) -> Result<&mut Self> {
+        self.options.set(key, value)?;
+        Ok(self)
+    }
+
+    /// Set multiple codec options at once.
+    pub fn options(&mut self, options: &Options) -> Result<&mut Self> {
+        self.options.extend(options)?;
+        Ok(self)
+    }
+
     pub fn open(&self, format: OutputFormat) -> Result<Encoder> {
         unsafe {
             let width = self.width.ok_or("Video encoder width not set")?;
@@ -190,37 +373,232 @@ impl EncoderBuilder {
 
             LibAV::init();
 
+            self.validate()?;
+            let global_quality = self.global_quality_lambda()?;
+
             let mut codec_context = avcodec_alloc_context3(self.codec.as_ptr());
             if codec_context.is_null() {
-                bail!("Could not allocate an encoding context");
+                bail!(ErrorKind::AllocFailed("encoding context"));
             }
 
             // Initialize encoder fields
             common::encoder::init(codec_context, format);
-            (*codec_context).codec_id = self.codec.id();
+            (*codec_context).codec_id = self.codec.id().as_raw();
             (*codec_context).width = width;
             (*codec_context).height = height;
-            (*codec_context).pix_fmt = pixel_format;
+            (*codec_context).pix_fmt = pixel_format.as_raw();
             if let Some(bitrate) = self.bitrate {
                 (*codec_context).bit_rate = bitrate;
             }
+            if let Some(max_rate) = self.max_rate {
+                (*codec_context).rc_max_rate = max_rate;
+            }
+            if let Some(min_rate) = self.min_rate {
+                (*codec_context).rc_min_rate = min_rate;
+            }
+            if let Some(buffer_size) = self.buffer_size {
+                (*codec_context).rc_buffer_size = buffer_size as c_int;
+            }
+            if let Some(gop_size) = self.gop_size {
+                (*codec_context).gop_size = gop_size as c_int;
+            }
+            if let Some(max_b_frames) = self.max_b_frames {
+                (*codec_context).max_b_frames = max_b_frames as c_int;
+            }
+            if let Some(qmin) = self.qmin {
+                (*codec_context).qmin = qmin as c_int;
+            }
+            if let Some(qmax) = self.qmax {
+                (*codec_context).qmax = qmax as c_int;
+            }
+            if let Some(quality) = global_quality {
+                (*codec_context).flags |= AV_CODEC_FLAG_QSCALE as c_int;
+                (*codec_context).global_quality = quality;
+            }
+            if let Some(profile) = self.profile {
+                (*codec_context).profile = profile;
+            }
+            if let Some(level) = self.level {
+                (*codec_context).level = level;
+            }
+            if let Some(thread_count) = self.thread_count {
+                (*codec_context).thread_count = thread_count as c_int;
+            }
+            if let Some(thread_type) = self.thread_type {
+                (*codec_context).thread_type = thread_type.bits();
+            }
+            if let Some(color_range) = self.color_range {
+                (*codec_context).color_range = color_range.as_raw();
+            }
+            if let Some(color_primaries) = self.color_primaries {
+                (*codec_context).color_primaries = color_primaries.as_raw();
+            }
+            if let Some(color_trc) = self.color_trc {
+                (*codec_context).color_trc = color_trc.as_raw();
+            }
+            if let Some(colorspace) = self.colorspace {
+                (*codec_context).colorspace = colorspace.as_raw();
+            }
+            if let Some(chroma_location) = self.chroma_location {
+                (*codec_context).chroma_sample_location = chroma_location.as_raw();
+            }
+            if let Some(crf) = self.crf {
+                if let Err(e) = set_private_option(codec_context, "crf", crf) {
+                    avcodec_free_context(&mut codec_context);
+                    return Err(e);
+                }
+            }
             // time_base: This is the fundamental unit of time (in seconds) in terms
             // of which frame timestamps are represented. For fixed-fps content,
             // time_base should be 1/framerate and timestamp increments should be
             // identical to 1.
             (*codec_context).time_base = time_base.into();
 
-            common::encoder::open(codec_context, "video")?;
+            let unused_options = common::encoder::open(codec_context, "video", &self.options)?;
 
             Ok(Encoder {
                 ptr: codec_context,
                 scaler: Scaler::new(),
                 tmp_frame: None,
+                unused_options: unused_options,
             })
         }
     }
 }
 
+impl EncoderBuilder {
+    /// Check the configured values against each other and the codec's capabilities.
+    fn validate(&self) -> Result<()> {
+        let codec_name = self.codec.name().unwrap_or_default().to_string_lossy();
+
+        for &(setting, value) in &[("bitrate", self.bitrate), ("max_rate", self.max_rate), ("min_rate", self.min_rate)] {
+            if let Some(value) = value {
+                if value <= 0 {
+                    return common::encoder::invalid_setting(setting, "must be positive");
+                }
+            }
+        }
+
+        if let (Some(bitrate), Some(max_rate)) = (self.bitrate, self.max_rate) {
+            if bitrate > max_rate {
+                return common::encoder::invalid_setting("bitrate", "must not exceed max_rate");
+            }
+        }
+        if let (Some(bitrate), Some(min_rate)) = (self.bitrate, self.min_rate) {
+            if bitrate < min_rate {
+                return common::encoder::invalid_setting("bitrate", "must not be below min_rate");
+            }
+        }
+        if let (Some(min_rate), Some(max_rate)) = (self.min_rate, self.max_rate) {
+            if min_rate > max_rate {
+                return common::encoder::invalid_setting("min_rate", "must not exceed max_rate");
+            }
+        }
+        if self.max_rate.is_some() && self.buffer_size.is_none() {
+            return common::encoder::invalid_setting("max_rate", "requires buffer_size to be set");
+        }
+        if self.buffer_size == Some(0) {
+            return common::encoder::invalid_setting("buffer_size", "must be positive");
+        }
+
+        for &(setting, value) in &[
+            ("buffer_size", self.buffer_size),
+            ("gop_size", self.gop_size),
+            ("max_b_frames", self.max_b_frames),
+            ("thread_count", self.thread_count),
+        ] {
+            if value.map_or(false, |value| value > c_int::max_value() as usize) {
+                return common::encoder::invalid_setting(setting, format!("must not exceed {}", c_int::max_value()));
+            }
+        }
+
+        for &(setting, value) in &[("qmin", self.qmin), ("qmax", self.qmax)] {
+            if value.map_or(false, |q| q > c_int::max_value() as u32) {
+                return common::encoder::invalid_setting(setting, format!("must not exceed {}", c_int::max_value()));
+            }
+        }
+        if let (Some(qmin), Some(qmax)) = (self.qmin, self.qmax) {
+            if qmin > qmax {
+                return common::encoder::invalid_setting("qmin", "must not exceed qmax");
+            }
+        }
+        if self.global_quality.is_some() && self.crf.is_some() {
+            return common::encoder::invalid_setting("global_quality", "cannot be combined with crf");
+        }
+        if self.crf.map_or(false, |crf| crf < 0.0) {
+            return common::encoder::invalid_setting("crf", "must not be negative");
+        }
+
+        if let Some(descriptor) = self.codec.descriptor() {
+            let props = descriptor.as_ref().props;
+
+            if 0 != (props & AV_CODEC_PROP_INTRA_ONLY as c_int) && self.gop_size.map_or(false, |size| size > 1) {
+                return common::encoder::invalid_setting("gop_size", format!("{} is an intra-only codec", codec_name));
+            }
+            if 0 == (props & AV_CODEC_PROP_REORDER as c_int) && self.max_b_frames.map_or(false, |frames| frames > 0) {
+                return common::encoder::invalid_setting("max_b_frames", format!("{} does not support B-frames", codec_name));
+            }
+        }
+
+        if let Some(profile) = self.profile {
+            common::encoder::require_supported_profile(self.codec, profile)?;
+        }
+        if self.level.map_or(false, |level| level <= 0) {
+            return common::encoder::invalid_setting("level", "must be positive");
+        }
+
+        if let Some(thread_type) = self.thread_type {
+            let capabilities = self.codec.capabilities();
+
+            if thread_type.contains(THREAD_FRAME) && !capabilities.contains(CAP_FRAME_THREADS) {
+                return common::encoder::invalid_setting("thread_type", format!("{} does not support frame threading", codec_name));
+            }
+            if thread_type.contains(THREAD_SLICE) && !capabilities.contains(CAP_SLICE_THREADS) {
+                return common::encoder::invalid_setting("thread_type", format!("{} does not support slice threading", codec_name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The `global_quality` in the lambda units used by libav.
+    fn global_quality_lambda(&self) -> Result<Option<c_int>> {
+        let quality = match self.global_quality {
+            Some(quality) => quality,
+            None => return Ok(None),
+        };
+
+        let lambda = if quality <= c_int::max_value() as u32 {
+            (quality as c_int).checked_mul(FF_QP2LAMBDA as c_int)
+        } else {
+            None
+        };
+
+        match lambda {
+            Some(lambda) => Ok(Some(lambda)),
+            None => bail!(ErrorKind::InvalidEncoderSetting("global_quality", format!("{} is too large", quality))),
+        }
+    }
+}
+
+/// Set a private option (e.g. `crf` of libx264) on an allocated codec context.
+unsafe fn set_private_option(codec_context: *mut AVCodecContext, name: &'static str, value: f64) -> Result<()> {
+    let c_name = CString::new(name).unwrap();
+
+    let priv_data = (*codec_context).priv_data;
+    if priv_data.is_null() || ffi::av_opt_find(priv_data, c_name.as_ptr(), ptr::null(), 0, 0).is_null() {
+        let codec_name = CStr::from_ptr((*(*codec_context).codec).name).to_string_lossy().into_owned();
+        bail!(ErrorKind::InvalidEncoderSetting(name, format!("{} has no {} option", codec_name, name)));
+    }
+
+    let res = ffi::av_opt_set_double(priv_data, c_name.as_ptr(), value, 0);
+    if res < 0 {
+        bail!(ErrorKind::av(res, format!("Could not set {}", name)));
+    }
+
+    Ok(())
+}
+
 pub struct Packets<'encoder> {
     encoder: OwnedOrRefMut<'encoder, Encoder>,
 }
@@ -252,7 +630,7 @@ impl<'encoder> Iterator for Packets<'encoder> {
 
                 match res {
                     ffi::AVERROR_EAGAIN | ffi::AVERROR_EOF => return None,
-                    _ => return Some(Err(format!("Failed to receive packet: 0x{:X}", res).into())),
+                    _ => return Some(Err(ErrorKind::av(res, "Failed to receive packet").into())),
                 }
             }
 
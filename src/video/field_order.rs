@@ -0,0 +1,15 @@
+raw_enum! {
+    /// The order of the fields of interlaced video.
+    pub enum FieldOrder(AVFieldOrder) {
+        Unknown = AV_FIELD_UNKNOWN,
+        Progressive = AV_FIELD_PROGRESSIVE,
+        /// Top coded first, top displayed first.
+        TopFirst = AV_FIELD_TT,
+        /// Bottom coded first, bottom displayed first.
+        BottomFirst = AV_FIELD_BB,
+        /// Top coded first, bottom displayed first.
+        TopCodedBottomFirst = AV_FIELD_TB,
+        /// Bottom coded first, top displayed first.
+        BottomCodedTopFirst = AV_FIELD_BT,
+    }
+}
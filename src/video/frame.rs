@@ -4,18 +4,31 @@ use smallvec::SmallVec;
 use ffi;
 use ffi::{
     AVFrame,
-    AVPixelFormat,
     av_frame_alloc,
     av_frame_free,
     av_frame_get_buffer,
 };
-use super::MAX_PLANES;
+use super::{
+    MAX_PLANES,
+    PixelFormat,
+    PictureType,
+    ColorRange,
+    ColorPrimaries,
+    ColorTransferCharacteristic,
+    ColorSpace,
+    ChromaLocation,
+};
 use video;
+use util::nonzero_rational;
+use common::{SideData, SideDataMut, Timebase};
 use errors::*;
 
+/// The buffer alignment used for frames allocated by this module.
+const ALIGN: usize = 32;
+
 pub struct Frame {
     ptr: *mut ffi::AVFrame,
-    pixel_format: AVPixelFormat,
+    pixel_format: PixelFormat,
 }
 
 // See https://github.com/panicbit/rust-av/issues/28
@@ -26,7 +39,7 @@ impl Frame {
     /// # Panics
     ///
     /// Panics if `width`, `height` or `align` exceed `c_int::max_value()`.
-    pub fn new(width: usize, height: usize, pixel_format: AVPixelFormat, align: usize) -> Result<Self> {
+    pub fn new(width: usize, height: usize, pixel_format: PixelFormat, align: usize) -> Result<Self> {
         unsafe {
             assert!(width <= c_int::max_value() as usize, "VideoFrame width exceeds c_int::max_value()");
             assert!(height <= c_int::max_value() as usize, "VideoFrame height exceeds c_int::max_value()");
@@ -34,12 +47,12 @@ impl Frame {
 
             let mut frame = av_frame_alloc();
             if frame.is_null() {
-                bail!("Could not allocate video frame");
+                bail!(ErrorKind::AllocFailed("video frame"));
             }
 
             // Fill in required information
             (*frame).pts = 0;
-            (*frame).format = pixel_format as c_int;
+            (*frame).format = pixel_format.as_raw() as c_int;
             (*frame).width = width as c_int;
             (*frame).height = height as c_int;
 
@@ -47,7 +60,7 @@ impl Frame {
             let res = av_frame_get_buffer(frame, align as c_int);
             if res < 0 {
                 av_frame_free(&mut frame);
-                bail!("Could not allocate video frame buffer: 0x{:X}", res);
+                bail!(ErrorKind::av(res, "Could not allocate video frame buffer"));
             }
 
             Ok(Self::from_ptr(frame, pixel_format))
@@ -56,7 +69,7 @@ impl Frame {
 }
 
 impl Frame {
-    pub fn pixel_format(&self) -> ffi::AVPixelFormat {
+    pub fn pixel_format(&self) -> PixelFormat {
         self.pixel_format
     }
 
@@ -88,6 +101,127 @@ impl Frame {
         self.as_mut().pts = pts;
     }
 
+    /// The timestamp estimated by the decoder using various heuristics.
+    /// Returns `None` if unknown.
+    pub fn best_effort_timestamp(&self) -> Option<i64> {
+        match self.as_ref().best_effort_timestamp {
+            ffi::NOPTS_VALUE => None,
+            ts => Some(ts),
+        }
+    }
+
+    /// The duration of the packet this frame was decoded from,
+    /// in the time base of the stream. `0` if unknown.
+    pub fn pkt_duration(&self) -> i64 {
+        self.as_ref().pkt_duration
+    }
+
+    pub fn set_pkt_duration(&mut self, duration: i64) {
+        self.as_mut().pkt_duration = duration;
+    }
+
+    pub fn is_key_frame(&self) -> bool {
+        self.as_ref().key_frame != 0
+    }
+
+    pub fn set_key_frame(&mut self, key_frame: bool) {
+        self.as_mut().key_frame = key_frame as c_int;
+    }
+
+    /// Set this to `PictureType::I` to force the encoder to produce a keyframe.
+    pub fn picture_type(&self) -> PictureType {
+        PictureType::from_raw(self.as_ref().pict_type)
+    }
+
+    pub fn set_picture_type(&mut self, picture_type: PictureType) {
+        self.as_mut().pict_type = picture_type.as_raw();
+    }
+
+    /// The sample aspect ratio. Returns `None` if unknown.
+    pub fn sample_aspect_ratio(&self) -> Option<Timebase> {
+        nonzero_rational(self.as_ref().sample_aspect_ratio)
+    }
+
+    pub fn set_sample_aspect_ratio(&mut self, sample_aspect_ratio: Option<Timebase>) {
+        self.as_mut().sample_aspect_ratio = sample_aspect_ratio.unwrap_or(Timebase::new(0, 1)).into();
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.as_ref().interlaced_frame != 0
+    }
+
+    pub fn set_interlaced(&mut self, interlaced: bool) {
+        self.as_mut().interlaced_frame = interlaced as c_int;
+    }
+
+    /// For interlaced frames, whether the top field is displayed first.
+    pub fn is_top_field_first(&self) -> bool {
+        self.as_ref().top_field_first != 0
+    }
+
+    pub fn set_top_field_first(&mut self, top_field_first: bool) {
+        self.as_mut().top_field_first = top_field_first as c_int;
+    }
+
+    /// How much the display of this frame must be delayed,
+    /// in units of half a frame duration.
+    pub fn repeat_pict(&self) -> c_int {
+        self.as_ref().repeat_pict
+    }
+
+    pub fn set_repeat_pict(&mut self, repeat_pict: c_int) {
+        self.as_mut().repeat_pict = repeat_pict;
+    }
+
+    pub fn color_range(&self) -> ColorRange {
+        ColorRange::from_raw(self.as_ref().color_range)
+    }
+
+    pub fn set_color_range(&mut self, color_range: ColorRange) {
+        self.as_mut().color_range = color_range.as_raw();
+    }
+
+    pub fn color_primaries(&self) -> ColorPrimaries {
+        ColorPrimaries::from_raw(self.as_ref().color_primaries)
+    }
+
+    pub fn set_color_primaries(&mut self, color_primaries: ColorPrimaries) {
+        self.as_mut().color_primaries = color_primaries.as_raw();
+    }
+
+    pub fn color_trc(&self) -> ColorTransferCharacteristic {
+        ColorTransferCharacteristic::from_raw(self.as_ref().color_trc)
+    }
+
+    pub fn set_color_trc(&mut self, color_trc: ColorTransferCharacteristic) {
+        self.as_mut().color_trc = color_trc.as_raw();
+    }
+
+    pub fn colorspace(&self) -> ColorSpace {
+        ColorSpace::from_raw(self.as_ref().colorspace)
+    }
+
+    pub fn set_colorspace(&mut self, colorspace: ColorSpace) {
+        self.as_mut().colorspace = colorspace.as_raw();
+    }
+
+    pub fn chroma_location(&self) -> ChromaLocation {
+        ChromaLocation::from_raw(self.as_ref().chroma_location)
+    }
+
+    pub fn set_chroma_location(&mut self, chroma_location: ChromaLocation) {
+        self.as_mut().chroma_location = chroma_location.as_raw();
+    }
+
+    /// Additional data attached to the frame, e.g. the display matrix or HDR metadata.
+    pub fn side_data(&self) -> SideData {
+        unsafe { SideData::from_ptr(self.ptr) }
+    }
+
+    pub fn side_data_mut(&mut self) -> SideDataMut {
+        unsafe { SideDataMut::from_ptr(self.ptr) }
+    }
+
     pub fn channel(&self, channel_index: usize) -> &[u8] {
         unsafe {
             let buf_len = self.height() * self.linesize(channel_index);
@@ -101,22 +235,21 @@ impl Frame {
         && self.height() == encoder.height()
     }
 
-    pub fn channel_mut(&mut self, channel_index: usize) -> &mut [u8] {
+    /// Mutable access to a plane.
+    /// The frame data is copied first if it is shared with other frames.
+    pub fn channel_mut(&mut self, channel_index: usize) -> Result<&mut [u8]> {
         unsafe {
-
-            if ffi::av_frame_make_writable(self.ptr) < 0 {
-                panic!("av_frame_make_writable failed (OOM?)");
-            }
+            self.make_writable()?;
 
             let buf_len = self.height() * self.linesize(channel_index);
 
-            slice::from_raw_parts_mut(self.as_mut().data[channel_index], buf_len)
+            Ok(slice::from_raw_parts_mut(self.as_mut().data[channel_index], buf_len))
         }
     }
 
     pub fn data(&self) -> SmallVec<[&[u8]; MAX_PLANES]> {
         unsafe {
-            let num_planes = ffi::av_pix_fmt_count_planes(self.pixel_format());
+            let num_planes = ffi::av_pix_fmt_count_planes(self.pixel_format().as_raw());
             if num_planes < 0 {
                 panic!("num planes negative (invalid pixel_format)");
             }
@@ -133,13 +266,13 @@ impl Frame {
         }
     }
 
-    pub fn data_mut(&self) -> SmallVec<[&mut [u8]; MAX_PLANES]> {
+    /// Mutable access to all planes.
+    /// The frame data is copied first if it is shared with other frames.
+    pub fn data_mut(&mut self) -> Result<SmallVec<[&mut [u8]; MAX_PLANES]>> {
         unsafe {
-            if ffi::av_frame_make_writable(self.ptr) < 0 {
-                panic!("av_frame_make_writable failed (OOM?)");
-            }
+            self.make_writable()?;
 
-            let num_planes = ffi::av_pix_fmt_count_planes(self.pixel_format());
+            let num_planes = ffi::av_pix_fmt_count_planes(self.pixel_format().as_raw());
             if num_planes < 0 {
                 panic!("num planes negative (invalid pixel_format)");
             }
@@ -152,33 +285,67 @@ impl Frame {
                 planes.push(plane);
             }
 
-            planes
+            Ok(planes)
         }
     }
 
     pub fn fill_channel(&mut self, channel_index: usize, source: &[u8]) -> Result<()> {
-        unsafe {
-            use std::cmp::min;
+        use std::cmp::min;
 
-            if ffi::av_frame_make_writable(self.ptr) < 0 {
-                panic!("av_frame_make_writable failed (OOM?)");
-            }
+        let source_linesize = source.len() / self.height();
+        let target_linesize = self.linesize(channel_index);
+        let linesize = min(source_linesize, target_linesize);
+        let channel = self.channel_mut(channel_index)?;
+
+        let source_lines = source.chunks(source_linesize);
+        let target_lines = channel.chunks_mut(target_linesize);
+
+        for (target, source) in target_lines.zip(source_lines) {
+            target[..linesize].copy_from_slice(&source[..linesize]);
+        }
 
-            let source_linesize = source.len() / self.height();
-            let target_linesize = self.linesize(channel_index);
-            let linesize = min(source_linesize, target_linesize);
-            let channel = self.channel_mut(channel_index);
+        Ok(())
+    }
+}
 
-            let source_lines = source.chunks(source_linesize);
-            let target_lines = channel.chunks_mut(target_linesize);
+impl Frame {
+    /// Whether the frame data is not shared with other frames.
+    pub fn is_writable(&self) -> bool {
+        unsafe { ffi::av_frame_is_writable(self.ptr) != 0 }
+    }
 
-            for (target, source) in target_lines.zip(source_lines) {
-                target[..linesize].copy_from_slice(&source[..linesize]);
+    /// Make sure the frame data is not shared with other frames,
+    /// copying it if necessary.
+    pub fn make_writable(&mut self) -> Result<()> {
+        unsafe {
+            let res = ffi::av_frame_make_writable(self.ptr);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not make video frame writable"));
             }
 
             Ok(())
         }
     }
+
+    /// Create a frame with its own copy of the data.
+    /// Unlike `clone`, the data is not shared.
+    pub fn copy(&self) -> Result<Self> {
+        unsafe {
+            let frame = Frame::new(self.width(), self.height(), self.pixel_format(), ALIGN)?;
+
+            let res = ffi::av_frame_copy(frame.ptr, self.ptr);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not copy video frame data"));
+            }
+
+            let res = ffi::av_frame_copy_props(frame.ptr, self.ptr);
+            if res < 0 {
+                bail!(ErrorKind::av(res, "Could not copy video frame properties"));
+            }
+
+            Ok(frame)
+        }
+    }
 }
 
 impl Frame {
@@ -190,13 +357,17 @@ impl Frame {
         unsafe { &mut *self.ptr }
     }
 
-    pub unsafe fn from_ptr(ptr: *mut AVFrame, pixel_format: AVPixelFormat) -> Self {
+    pub unsafe fn from_ptr(ptr: *mut AVFrame, pixel_format: PixelFormat) -> Self {
         Frame {
             ptr: ptr,
             pixel_format: pixel_format,
         }
     }
 
+    pub fn as_ptr(&self) -> *const AVFrame {
+        self.ptr
+    }
+
     pub fn as_mut_ptr(&mut self) -> *mut AVFrame {
         self.ptr
     }
@@ -206,6 +377,30 @@ impl Frame {
     }
 }
 
+/// Creates a new reference to the same frame data.
+///
+/// # Panics
+///
+/// Panics if the frame can't be allocated (OOM).
+impl Clone for Frame {
+    fn clone(&self) -> Self {
+        unsafe {
+            let mut frame = av_frame_alloc();
+            if frame.is_null() {
+                panic!("Could not allocate video frame (OOM?)");
+            }
+
+            let res = ffi::av_frame_ref(frame, self.ptr);
+            if res < 0 {
+                av_frame_free(&mut frame);
+                panic!("Could not reference video frame: {}", av_strerror(res));
+            }
+
+            Self::from_ptr(frame, self.pixel_format)
+        }
+    }
+}
+
 impl Drop for Frame {
     fn drop(&mut self) {
         unsafe {
@@ -213,3 +408,30 @@ impl Drop for Frame {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use video::PixelFormat;
+    use super::Frame;
+
+    #[test]
+    fn copy() {
+        let (width, height) = (5, 3);
+        let mut frame = Frame::new(width, height, PixelFormat::Rgb24, 1).unwrap();
+        let source: Vec<u8> = (0..width * height * 3).map(|i| i as u8).collect();
+        frame.fill_channel(0, &source).unwrap();
+
+        let copy = frame.copy().unwrap();
+
+        assert_eq!(copy.width(), width);
+        assert_eq!(copy.height(), height);
+        assert_eq!(copy.pixel_format(), PixelFormat::Rgb24);
+        assert!(copy.channel(0).as_ptr() != frame.channel(0).as_ptr());
+
+        let lines = frame.channel(0).chunks(frame.linesize(0));
+        let copied_lines = copy.channel(0).chunks(copy.linesize(0));
+        for (line, copied_line) in lines.zip(copied_lines) {
+            assert_eq!(&line[..width * 3], &copied_line[..width * 3]);
+        }
+    }
+}
@@ -17,4 +17,32 @@ mod frame;
 pub use self::frame::Frame;
 
 mod scaler;
-pub use self::scaler::Scaler;
+pub use self::scaler::{
+    Scaler,
+    ScalerOptions,
+    ScalingAlgorithm,
+    ScaleRegion,
+    AspectMode,
+    Rect,
+};
+
+mod pixel_format;
+pub use self::pixel_format::{
+    PixelFormat,
+    PixelFormatDescriptor,
+};
+
+mod color;
+pub use self::color::{
+    ColorRange,
+    ColorPrimaries,
+    ColorTransferCharacteristic,
+    ColorSpace,
+    ChromaLocation,
+};
+
+mod picture_type;
+pub use self::picture_type::PictureType;
+
+mod field_order;
+pub use self::field_order::FieldOrder;
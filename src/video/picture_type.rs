@@ -0,0 +1,19 @@
+raw_enum! {
+    pub enum PictureType(AVPictureType) {
+        None = AV_PICTURE_TYPE_NONE,
+        /// Intra
+        I    = AV_PICTURE_TYPE_I,
+        /// Predicted
+        P    = AV_PICTURE_TYPE_P,
+        /// Bi-directionally predicted
+        B    = AV_PICTURE_TYPE_B,
+        /// S(GMC)-VOP MPEG-4
+        S    = AV_PICTURE_TYPE_S,
+        /// Switching intra
+        Si   = AV_PICTURE_TYPE_SI,
+        /// Switching predicted
+        Sp   = AV_PICTURE_TYPE_SP,
+        /// BI type
+        Bi   = AV_PICTURE_TYPE_BI,
+    }
+}
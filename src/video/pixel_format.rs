@@ -0,0 +1,247 @@
+use std::fmt;
+use std::ptr;
+use std::str::FromStr;
+use std::ffi::{CStr, CString};
+use ffi::{
+    self,
+    AVPixFmtDescriptor,
+    AV_PIX_FMT_FLAG_BE,
+    AV_PIX_FMT_FLAG_PAL,
+    AV_PIX_FMT_FLAG_HWACCEL,
+    AV_PIX_FMT_FLAG_PLANAR,
+    AV_PIX_FMT_FLAG_RGB,
+    AV_PIX_FMT_FLAG_ALPHA,
+    AV_PIX_FMT_FLAG_BITSTREAM,
+};
+use util::AsCStr;
+use errors::*;
+
+raw_enum! {
+    pub enum PixelFormat(AVPixelFormat) {
+        None         = AV_PIX_FMT_NONE,
+        Yuv420p      = AV_PIX_FMT_YUV420P,
+        Yuyv422      = AV_PIX_FMT_YUYV422,
+        Rgb24        = AV_PIX_FMT_RGB24,
+        Bgr24        = AV_PIX_FMT_BGR24,
+        Yuv422p      = AV_PIX_FMT_YUV422P,
+        Yuv444p      = AV_PIX_FMT_YUV444P,
+        Yuv410p      = AV_PIX_FMT_YUV410P,
+        Yuv411p      = AV_PIX_FMT_YUV411P,
+        Gray8        = AV_PIX_FMT_GRAY8,
+        MonoWhite    = AV_PIX_FMT_MONOWHITE,
+        MonoBlack    = AV_PIX_FMT_MONOBLACK,
+        Pal8         = AV_PIX_FMT_PAL8,
+        Yuvj420p     = AV_PIX_FMT_YUVJ420P,
+        Yuvj422p     = AV_PIX_FMT_YUVJ422P,
+        Yuvj444p     = AV_PIX_FMT_YUVJ444P,
+        Uyvy422      = AV_PIX_FMT_UYVY422,
+        Nv12         = AV_PIX_FMT_NV12,
+        Nv21         = AV_PIX_FMT_NV21,
+        Argb         = AV_PIX_FMT_ARGB,
+        Rgba         = AV_PIX_FMT_RGBA,
+        Abgr         = AV_PIX_FMT_ABGR,
+        Bgra         = AV_PIX_FMT_BGRA,
+        Gray16be     = AV_PIX_FMT_GRAY16BE,
+        Gray16le     = AV_PIX_FMT_GRAY16LE,
+        Yuv440p      = AV_PIX_FMT_YUV440P,
+        Yuvj440p     = AV_PIX_FMT_YUVJ440P,
+        Yuva420p     = AV_PIX_FMT_YUVA420P,
+        Rgb48be      = AV_PIX_FMT_RGB48BE,
+        Rgb48le      = AV_PIX_FMT_RGB48LE,
+        Rgb565be     = AV_PIX_FMT_RGB565BE,
+        Rgb565le     = AV_PIX_FMT_RGB565LE,
+        Rgb555be     = AV_PIX_FMT_RGB555BE,
+        Rgb555le     = AV_PIX_FMT_RGB555LE,
+        Yuv420p16le  = AV_PIX_FMT_YUV420P16LE,
+        Yuv420p16be  = AV_PIX_FMT_YUV420P16BE,
+        Yuv422p16le  = AV_PIX_FMT_YUV422P16LE,
+        Yuv422p16be  = AV_PIX_FMT_YUV422P16BE,
+        Yuv444p16le  = AV_PIX_FMT_YUV444P16LE,
+        Yuv444p16be  = AV_PIX_FMT_YUV444P16BE,
+        Yuv420p10be  = AV_PIX_FMT_YUV420P10BE,
+        Yuv420p10le  = AV_PIX_FMT_YUV420P10LE,
+        Yuv422p10be  = AV_PIX_FMT_YUV422P10BE,
+        Yuv422p10le  = AV_PIX_FMT_YUV422P10LE,
+        Yuv444p10be  = AV_PIX_FMT_YUV444P10BE,
+        Yuv444p10le  = AV_PIX_FMT_YUV444P10LE,
+        Yuv420p12be  = AV_PIX_FMT_YUV420P12BE,
+        Yuv420p12le  = AV_PIX_FMT_YUV420P12LE,
+        Yuv422p12be  = AV_PIX_FMT_YUV422P12BE,
+        Yuv422p12le  = AV_PIX_FMT_YUV422P12LE,
+        Yuv444p12be  = AV_PIX_FMT_YUV444P12BE,
+        Yuv444p12le  = AV_PIX_FMT_YUV444P12LE,
+        Gbrp         = AV_PIX_FMT_GBRP,
+        Gbrp10be     = AV_PIX_FMT_GBRP10BE,
+        Gbrp10le     = AV_PIX_FMT_GBRP10LE,
+        Gbrap        = AV_PIX_FMT_GBRAP,
+        Ya8          = AV_PIX_FMT_YA8,
+        Yuva422p     = AV_PIX_FMT_YUVA422P,
+        Yuva444p     = AV_PIX_FMT_YUVA444P,
+        Rgba64be     = AV_PIX_FMT_RGBA64BE,
+        Rgba64le     = AV_PIX_FMT_RGBA64LE,
+        Bgra64be     = AV_PIX_FMT_BGRA64BE,
+        Bgra64le     = AV_PIX_FMT_BGRA64LE,
+        Rgb0         = AV_PIX_FMT_RGB0,
+        Bgr0         = AV_PIX_FMT_BGR0,
+        Nv16         = AV_PIX_FMT_NV16,
+        Xyz12le      = AV_PIX_FMT_XYZ12LE,
+        Xyz12be      = AV_PIX_FMT_XYZ12BE,
+    }
+}
+
+impl PixelFormat {
+    /// The libav name of this format, e.g. `yuv420p`.
+    pub fn name(self) -> Option<&'static str> {
+        unsafe {
+            ffi::av_get_pix_fmt_name(self.as_raw()).as_cstr()
+                .and_then(|name| name.to_str().ok())
+        }
+    }
+
+    /// Returns `None` for `PixelFormat::None`.
+    pub fn descriptor(self) -> Option<PixelFormatDescriptor> {
+        unsafe {
+            let descriptor = ffi::av_pix_fmt_desc_get(self.as_raw());
+            if descriptor.is_null() {
+                None
+            } else {
+                Some(PixelFormatDescriptor::from_ptr(descriptor))
+            }
+        }
+    }
+}
+
+impl fmt::Display for PixelFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name().unwrap_or("none"))
+    }
+}
+
+impl FromStr for PixelFormat {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        unsafe {
+            let c_name = CString::new(name)
+                .map_err(|_| format!("Invalid pixel format name {:?}", name))?;
+
+            match ffi::av_get_pix_fmt(c_name.as_ptr()) {
+                ffi::AVPixelFormat::AV_PIX_FMT_NONE if name != "none" => bail!("Unknown pixel format {:?}", name),
+                format => Ok(PixelFormat::from_raw(format)),
+            }
+        }
+    }
+}
+
+/// Describes the memory layout of a pixel format.
+#[derive(Copy,Clone)]
+pub struct PixelFormatDescriptor {
+    ptr: *const AVPixFmtDescriptor,
+}
+
+impl PixelFormatDescriptor {
+    pub unsafe fn from_ptr(ptr: *const AVPixFmtDescriptor) -> Self {
+        PixelFormatDescriptor { ptr: ptr }
+    }
+
+    pub fn as_ref(&self) -> &AVPixFmtDescriptor {
+        unsafe { &*self.ptr }
+    }
+
+    pub fn name(&self) -> &CStr {
+        unsafe { self.as_ref().name.as_cstr().unwrap() }
+    }
+
+    /// The number of components (e.g. 3 for YUV, 4 for RGBA).
+    pub fn num_components(&self) -> usize {
+        self.as_ref().nb_components as usize
+    }
+
+    /// The number of data planes.
+    pub fn num_planes(&self) -> usize {
+        unsafe {
+            let format = ffi::av_pix_fmt_desc_get_id(self.ptr);
+            ffi::av_pix_fmt_count_planes(format).max(0) as usize
+        }
+    }
+
+    /// The number of bits per pixel used for storing the pixel information,
+    /// ignoring padding.
+    pub fn bits_per_pixel(&self) -> usize {
+        unsafe { ffi::av_get_bits_per_pixel(self.ptr) as usize }
+    }
+
+    /// The number of bits per pixel including padding.
+    pub fn padded_bits_per_pixel(&self) -> usize {
+        unsafe { ffi::av_get_padded_bits_per_pixel(self.ptr) as usize }
+    }
+
+    /// The chroma subsampling as `log2` of the horizontal and vertical factors,
+    /// e.g. `(1, 1)` for 4:2:0.
+    pub fn chroma_subsampling(&self) -> (u8, u8) {
+        (self.as_ref().log2_chroma_w, self.as_ref().log2_chroma_h)
+    }
+
+    /// The maximum number of bytes between two horizontally adjacent pixels of each plane.
+    /// For packed formats with subsampled chroma this is the size of a macropixel.
+    pub fn max_pixel_steps(&self) -> [usize; 4] {
+        unsafe {
+            let mut steps = [0; 4];
+            ffi::av_image_fill_max_pixsteps(steps.as_mut_ptr(), ptr::null_mut(), self.ptr);
+
+            let mut result = [0; 4];
+            for (result, &step) in result.iter_mut().zip(&steps) {
+                *result = step as usize;
+            }
+            result
+        }
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_ALPHA as u64)
+    }
+
+    pub fn is_big_endian(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_BE as u64)
+    }
+
+    pub fn is_planar(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_PLANAR as u64)
+    }
+
+    pub fn is_rgb(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_RGB as u64)
+    }
+
+    pub fn is_palette(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_PAL as u64)
+    }
+
+    /// Whether the pixels are packed into bits instead of bytes (e.g. `monob`).
+    pub fn is_bitstream(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_BITSTREAM as u64)
+    }
+
+    /// Whether this is a hardware format whose data is not accessible in memory.
+    pub fn is_hwaccel(&self) -> bool {
+        self.has_flag(AV_PIX_FMT_FLAG_HWACCEL as u64)
+    }
+
+    fn has_flag(&self, flag: u64) -> bool {
+        0 != (self.as_ref().flags as u64 & flag)
+    }
+}
+
+impl fmt::Debug for PixelFormatDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PixelFormatDescriptor")
+            .field("name", &self.name())
+            .field("num_components", &self.num_components())
+            .field("num_planes", &self.num_planes())
+            .field("bits_per_pixel", &self.bits_per_pixel())
+            .field("chroma_subsampling", &self.chroma_subsampling())
+            .field("has_alpha", &self.has_alpha())
+            .field("is_big_endian", &self.is_big_endian())
+            .finish()
+    }
+}
@@ -1,8 +1,99 @@
 use std::ptr;
+use std::os::raw::c_int;
 use smallvec::SmallVec;
-use ffi::{self,AVPixelFormat};
+use ffi;
 use errors::*;
-use super::{Frame, MAX_PLANES};
+use super::{Frame, PixelFormat, ColorSpace, ColorRange, MAX_PLANES};
+
+/// The interpolation used for scaling.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub enum ScalingAlgorithm {
+    FastBilinear,
+    Bilinear,
+    Bicubic,
+    /// Nearest neighbor.
+    Point,
+    /// Averaging area, useful for downscaling.
+    Area,
+    Lanczos,
+    Spline,
+}
+
+impl ScalingAlgorithm {
+    fn flags(self) -> c_int {
+        let flags = match self {
+            ScalingAlgorithm::FastBilinear => ffi::SWS_FAST_BILINEAR,
+            ScalingAlgorithm::Bilinear     => ffi::SWS_BILINEAR,
+            ScalingAlgorithm::Bicubic      => ffi::SWS_BICUBIC,
+            ScalingAlgorithm::Point        => ffi::SWS_POINT,
+            ScalingAlgorithm::Area         => ffi::SWS_AREA,
+            ScalingAlgorithm::Lanczos      => ffi::SWS_LANCZOS,
+            ScalingAlgorithm::Spline       => ffi::SWS_SPLINE,
+        };
+        flags as c_int
+    }
+}
+
+/// Options controlling the quality and color conversion of a `Scaler`.
+///
+/// Unset colorspaces and ranges use the swscale defaults (BT.601, limited range
+/// unless implied by the pixel format). `Scaler::scale_frame` takes them from
+/// the frames instead.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct ScalerOptions {
+    pub algorithm: ScalingAlgorithm,
+    /// Use accurate rounding at the cost of speed.
+    pub accurate_rounding: bool,
+    /// Use full chroma resolution when converting from and to RGB,
+    /// instead of interpolating the subsampled chroma.
+    pub full_chroma: bool,
+    /// The YUV matrix of the source, e.g. `ColorSpace::Bt709`.
+    pub src_colorspace: Option<ColorSpace>,
+    /// The YUV matrix of the destination.
+    pub dst_colorspace: Option<ColorSpace>,
+    /// Whether the source uses the full (`Jpeg`) or limited (`Mpeg`) range.
+    pub src_range: Option<ColorRange>,
+    /// Whether the destination uses the full (`Jpeg`) or limited (`Mpeg`) range.
+    pub dst_range: Option<ColorRange>,
+}
+
+impl ScalerOptions {
+    fn flags(&self) -> c_int {
+        let mut flags = self.algorithm.flags();
+        if self.accurate_rounding {
+            flags |= ffi::SWS_ACCURATE_RND as c_int;
+        }
+        if self.full_chroma {
+            flags |= (ffi::SWS_FULL_CHR_H_INT | ffi::SWS_FULL_CHR_H_INP) as c_int;
+        }
+        flags
+    }
+
+    /// Fill in the unset colorspaces and ranges from the frames.
+    fn or_from_frames(&self, src: &Frame, dst: &Frame) -> Self {
+        ScalerOptions {
+            src_colorspace: self.src_colorspace.or(Some(src.colorspace())),
+            dst_colorspace: self.dst_colorspace.or(Some(dst.colorspace())),
+            src_range: self.src_range.or(Some(src.color_range())),
+            dst_range: self.dst_range.or(Some(dst.color_range())),
+            .. *self
+        }
+    }
+}
+
+impl Default for ScalerOptions {
+    fn default() -> Self {
+        ScalerOptions {
+            algorithm: ScalingAlgorithm::Bicubic,
+            accurate_rounding: false,
+            full_chroma: false,
+            src_colorspace: None,
+            dst_colorspace: None,
+            src_range: None,
+            dst_range: None,
+        }
+    }
+}
 
 /// A context for scaling/converting video frames.
 ///
@@ -10,15 +101,18 @@ use super::{Frame, MAX_PLANES};
 /// for a specific combination of source/target width, height and format.
 /// It's allowed to use different values for each invocation of the scaling
 /// functions, but it will result in reallocation of the internal scaling context,
-/// which might not be desireable.
+/// which might not be desireable. The same applies to changing the options.
 pub struct Scaler {
+    options: ScalerOptions,
     context: Option<SwsContext>,
+    context_options: ScalerOptions,
+    pad_scaler: Option<Box<Scaler>>,
     src_w: usize,
     src_h: usize,
-    src_fmt: AVPixelFormat,
+    src_fmt: PixelFormat,
     dst_w: usize,
     dst_h: usize,
-    dst_fmt: AVPixelFormat,
+    dst_fmt: PixelFormat,
 }
 
 unsafe impl Send for Scaler{}
@@ -27,35 +121,58 @@ unsafe impl Sync for Scaler{}
 impl Scaler {
     /// Create a new scaling context.
     pub fn new() -> Self {
+        Self::with_options(ScalerOptions::default())
+    }
+
+    pub fn with_options(options: ScalerOptions) -> Self {
         Scaler {
+            options: options,
             context: None,
+            context_options: options,
+            pad_scaler: None,
             src_w: 0,
             src_h: 0,
-            src_fmt: AVPixelFormat::AV_PIX_FMT_RGB24,
+            src_fmt: PixelFormat::Rgb24,
             dst_w: 0,
             dst_h: 0,
-            dst_fmt: AVPixelFormat::AV_PIX_FMT_RGB24,
+            dst_fmt: PixelFormat::Rgb24,
         }
     }
 
+    pub fn options(&self) -> ScalerOptions {
+        self.options
+    }
+
+    /// The context is rebuilt on the next use if the options changed.
+    pub fn set_options(&mut self, options: ScalerOptions) {
+        self.options = options;
+    }
+
     /// Actually initialize the context and
     /// reinitialize it if needed.
     fn init_context(&mut self,
-        src_w: usize, src_h: usize, src_fmt: AVPixelFormat,
-        dst_w: usize, dst_h: usize, dst_fmt: AVPixelFormat,
+        src_w: usize, src_h: usize, src_fmt: PixelFormat,
+        dst_w: usize, dst_h: usize, dst_fmt: PixelFormat,
+        options: ScalerOptions,
     ) -> Result<&mut SwsContext> {
         // (Re)allocate
         if    self.context.is_none()
            || self.src_w   != src_w   || self.dst_w   != dst_w
            || self.src_h   != src_h   || self.dst_h   != dst_h
            || self.src_fmt != src_fmt || self.dst_fmt != dst_fmt
+           || self.context_options != options
         {
-            let flags = ffi::SWS_BICUBIC as i32;
-            self.context = Some(SwsContext::new(
+            // Drop the old context first, in case creating the new one fails
+            self.context = None;
+
+            let mut context = SwsContext::new(
                 src_w as i32, src_h as i32, src_fmt,
                 dst_w as i32, dst_h as i32, dst_fmt,
-                flags
-            )?);
+                options.flags()
+            )?;
+            context.set_colorspace_details(&options)?;
+            self.context = Some(context);
+            self.context_options = options;
 
             self.src_h   = src_h;
             self.src_w   = src_w;
@@ -79,18 +196,44 @@ impl Scaler {
     /// - The planes need to be big enough to contain the amount of bytes
     ///   described by their linesize and the height (`linesize * height`).
     pub fn scale(&mut self,
-        src_data: &    [&    [u8]], src_linesize: &[usize], src_w: usize, src_h: usize, src_fmt: AVPixelFormat,
-        dst_data: &mut [&mut [u8]], dst_linesize: &[usize], dst_w: usize, dst_h: usize, dst_fmt: AVPixelFormat,
+        src_data: &    [&    [u8]], src_linesize: &[usize], src_w: usize, src_h: usize, src_fmt: PixelFormat,
+        dst_data: &mut [&mut [u8]], dst_linesize: &[usize], dst_w: usize, dst_h: usize, dst_fmt: PixelFormat,
     ) -> Result<()> {
-        unsafe {
-            // Get appropriate scaling context
-            let context = self.init_context(
-                src_w, src_h, src_fmt,
-                dst_w, dst_h, dst_fmt,
-            )?;
+        self.scale_region(
+            src_data, src_linesize, src_w, src_h, src_fmt,
+            dst_data, dst_linesize, dst_w, dst_h, dst_fmt,
+            &ScaleRegion::default(),
+        )
+    }
 
-            let src_num_planes = ffi::av_pix_fmt_count_planes(src_fmt);
-            let dst_num_planes = ffi::av_pix_fmt_count_planes(dst_fmt);
+    /// Like `scale`, but scale the `src_crop` region of the source
+    /// into the `dst_rect` region of the destination,
+    /// keeping the aspect ratio as requested by the region's `aspect`.
+    ///
+    /// Pixels are assumed to be square.
+    pub fn scale_region(&mut self,
+        src_data: &    [&    [u8]], src_linesize: &[usize], src_w: usize, src_h: usize, src_fmt: PixelFormat,
+        dst_data: &mut [&mut [u8]], dst_linesize: &[usize], dst_w: usize, dst_h: usize, dst_fmt: PixelFormat,
+        region: &ScaleRegion,
+    ) -> Result<()> {
+        let options = self.options;
+        let sample_aspect_ratios = (1.0, 1.0);
+
+        self.scale_region_with_options(
+            src_data, src_linesize, src_w, src_h, src_fmt,
+            dst_data, dst_linesize, dst_w, dst_h, dst_fmt,
+            region, sample_aspect_ratios, options,
+        )
+    }
+
+    fn scale_region_with_options(&mut self,
+        src_data: &    [&    [u8]], src_linesize: &[usize], src_w: usize, src_h: usize, src_fmt: PixelFormat,
+        dst_data: &mut [&mut [u8]], dst_linesize: &[usize], dst_w: usize, dst_h: usize, dst_fmt: PixelFormat,
+        region: &ScaleRegion, (src_sar, dst_sar): (f64, f64), options: ScalerOptions,
+    ) -> Result<()> {
+        unsafe {
+            let src_num_planes = ffi::av_pix_fmt_count_planes(src_fmt.as_raw());
+            let dst_num_planes = ffi::av_pix_fmt_count_planes(dst_fmt.as_raw());
 
             if src_num_planes <= 0 || dst_num_planes <= 0 {
                 bail!("Invalid pixel format for scale source or target");
@@ -125,6 +268,11 @@ impl Scaler {
                 }
             }
 
+            let layout = region.layout(
+                (src_w, src_h), chroma_alignment(src_fmt), src_sar,
+                (dst_w, dst_h), chroma_alignment(dst_fmt), dst_sar,
+            )?;
+
             // Convert the slices to the proper ffmpeg types
             let mut src_data: SmallVec<[*const u8; MAX_PLANES]> = src_data.iter().map(| s| s.as_ptr()).collect();
             let mut dst_data: SmallVec<[*const u8; MAX_PLANES]> = dst_data.iter().map(| s| s.as_ptr()).collect();
@@ -137,45 +285,334 @@ impl Scaler {
             while src_linesize.len() < 4 { src_linesize.push(0) }
             while dst_linesize.len() < 4 { dst_linesize.push(0) }
 
-            let source_y = 0;
+            if region.aspect == AspectMode::Letterbox && layout.dst != layout.dst_outer {
+                let mut pad_data = offset_planes(&dst_data, &dst_linesize, dst_fmt, layout.dst_outer)?;
+                if self.pad_scaler.is_none() {
+                    self.pad_scaler = Some(Box::new(Scaler::new()));
+                }
+                let pad_scaler = self.pad_scaler.as_mut().unwrap();
 
-            ffi::sws_scale(context.as_mut_ptr(),
-                src_data.as_ptr()    , src_linesize.as_ptr(), source_y, src_h as i32,
-                dst_data.as_mut_ptr(), dst_linesize.as_ptr(),
-            );
+                pad_scaler.fill(&mut pad_data, &dst_linesize, layout.dst_outer, dst_fmt, region.pad_color, options)?;
+            }
 
-            Ok(())
+            let src_data = offset_planes(&src_data, &src_linesize, src_fmt, layout.src)?;
+            let mut dst_data = offset_planes(&dst_data, &dst_linesize, dst_fmt, layout.dst)?;
+
+            self.scale_raw(
+                &src_data, &src_linesize, layout.src.width, layout.src.height, src_fmt,
+                &mut dst_data, &dst_linesize, layout.dst.width, layout.dst.height, dst_fmt,
+                options,
+            )
         }
     }
 
+    /// Fill `rect` of the destination planes with an RGB color.
+    unsafe fn fill(&mut self,
+        dst_data: &mut [*const u8], dst_linesize: &[i32], rect: Rect, dst_fmt: PixelFormat,
+        (r, g, b): (u8, u8, u8), options: ScalerOptions,
+    ) -> Result<()> {
+        // Use a few pixels, since swscale may read ahead
+        const SIZE: usize = 16;
+        let mut pixels = vec![0; SIZE * SIZE * 3];
+        for pixel in pixels.chunks_mut(3) {
+            pixel.copy_from_slice(&[r, g, b]);
+        }
+
+        let src_data = [pixels.as_ptr(), ptr::null(), ptr::null(), ptr::null()];
+        let src_linesize = [(SIZE * 3) as i32, 0, 0, 0];
+        let options = ScalerOptions {
+            algorithm: ScalingAlgorithm::Point,
+            src_colorspace: None,
+            src_range: None,
+            .. options
+        };
+
+        self.scale_raw(
+            &src_data, &src_linesize, SIZE, SIZE, PixelFormat::Rgb24,
+            dst_data, dst_linesize, rect.width, rect.height, dst_fmt,
+            options,
+        )
+    }
+
+    unsafe fn scale_raw(&mut self,
+        src_data: &    [*const u8], src_linesize: &[i32], src_w: usize, src_h: usize, src_fmt: PixelFormat,
+        dst_data: &mut [*const u8], dst_linesize: &[i32], dst_w: usize, dst_h: usize, dst_fmt: PixelFormat,
+        options: ScalerOptions,
+    ) -> Result<()> {
+        // Get appropriate scaling context
+        let context = self.init_context(
+            src_w, src_h, src_fmt,
+            dst_w, dst_h, dst_fmt,
+            options,
+        )?;
+
+        let source_y = 0;
+
+        ffi::sws_scale(context.as_mut_ptr(),
+            src_data.as_ptr()    , src_linesize.as_ptr(), source_y, src_h as i32,
+            dst_data.as_mut_ptr(), dst_linesize.as_ptr(),
+        );
+
+        Ok(())
+    }
+
     /// Copy the `src` pixel data to the `dst` pixel data,
     /// scaling dimensions and converting pixel formats as required.
+    ///
+    /// Colorspaces and ranges that are not set in the options
+    /// are taken from the color properties of the frames.
     pub fn scale_frame(&mut self, src: &Frame, dst: &mut Frame) -> Result<()> {
+        self.scale_frame_region(src, dst, &ScaleRegion::default())
+    }
+
+    /// Like `scale_frame`, but scale between regions of the frames as in `scale_region`.
+    /// Non-square pixels are taken into account using the sample aspect ratios of the frames.
+    pub fn scale_frame_region(&mut self, src: &Frame, dst: &mut Frame, region: &ScaleRegion) -> Result<()> {
+        let options      =  self.options.or_from_frames(src, dst);
+        let sample_aspect_ratios = (sample_aspect_ratio(src), sample_aspect_ratio(dst));
+
         let src_data     = &src.data();
         let src_linesize = &src.linesizes();
         let src_h        =  src.height();
         let src_w        =  src.width();
         let src_fmt      =  src.pixel_format();
 
-        let dst_data     = &mut dst.data_mut();
         let dst_linesize = &    dst.linesizes();
         let dst_h        =      dst.height();
         let dst_w        =      dst.width();
         let dst_fmt      =      dst.pixel_format();
+        let dst_data     = &mut dst.data_mut()?;
 
-        self.scale(
+        self.scale_region_with_options(
             src_data, src_linesize, src_w, src_h, src_fmt,
             dst_data, dst_linesize, dst_w, dst_h, dst_fmt,
+            region, sample_aspect_ratios, options,
         )
     }
 }
 
+fn sample_aspect_ratio(frame: &Frame) -> f64 {
+    frame.sample_aspect_ratio()
+        .map(|sample_aspect_ratio| sample_aspect_ratio.as_f64())
+        .unwrap_or(1.0)
+}
+
+/// The horizontal and vertical alignment of pixel positions
+/// required by the chroma subsampling of `fmt`.
+fn chroma_alignment(fmt: PixelFormat) -> (usize, usize) {
+    fmt.descriptor()
+        .map(|descriptor| {
+            let (log2_w, log2_h) = descriptor.chroma_subsampling();
+            (1 << log2_w, 1 << log2_h)
+        })
+        .unwrap_or((1, 1))
+}
+
+/// Offset the plane pointers to the top-left pixel of `rect`.
+/// The position of `rect` needs to be aligned to the chroma subsampling.
+unsafe fn offset_planes(data: &[*const u8], linesizes: &[i32], fmt: PixelFormat, rect: Rect) -> Result<SmallVec<[*const u8; MAX_PLANES]>> {
+    let mut data: SmallVec<[*const u8; MAX_PLANES]> = data.iter().cloned().collect();
+
+    if rect.x == 0 && rect.y == 0 {
+        return Ok(data);
+    }
+
+    let descriptor = match fmt.descriptor() {
+        Some(descriptor) => descriptor,
+        None => bail!("Invalid pixel format {}", fmt),
+    };
+
+    if descriptor.is_hwaccel() || descriptor.is_bitstream() {
+        bail!("Cannot scale a region of pixel format {}", fmt);
+    }
+
+    let (log2_chroma_w, log2_chroma_h) = descriptor.chroma_subsampling();
+    let num_planes = descriptor.num_planes();
+    let steps = descriptor.max_pixel_steps();
+
+    for plane in 0..num_planes {
+        // For packed formats with subsampled chroma the step covers multiple pixels
+        let is_chroma = plane == 1 || plane == 2;
+        let shift_w = if is_chroma || num_planes == 1 { log2_chroma_w } else { 0 };
+        let shift_h = if is_chroma { log2_chroma_h } else { 0 };
+
+        let offset = (rect.y >> shift_h) as isize * linesizes[plane] as isize
+                   + ((rect.x >> shift_w) * steps[plane]) as isize;
+        data[plane] = data[plane].offset(offset);
+    }
+
+    Ok(data)
+}
+
+/// A rectangle in pixels.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Rect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    fn is_inside(&self, (width, height): (usize, usize)) -> bool {
+        let right = match self.x.checked_add(self.width) {
+            Some(right) => right,
+            None => return false,
+        };
+        let bottom = match self.y.checked_add(self.height) {
+            Some(bottom) => bottom,
+            None => return false,
+        };
+
+        self.width > 0 && self.height > 0 && right <= width && bottom <= height
+    }
+
+    /// Move the rectangle up and left to an aligned position.
+    fn align(&self, (align_x, align_y): (usize, usize)) -> Rect {
+        Rect {
+            x: self.x - self.x % align_x,
+            y: self.y - self.y % align_y,
+            .. *self
+        }
+    }
+
+    /// A rectangle of the given size centered inside this aligned rectangle.
+    fn centered(&self, width: f64, height: f64, align: (usize, usize)) -> Rect {
+        let width = (width.round() as usize).max(1).min(self.width);
+        let height = (height.round() as usize).max(1).min(self.height);
+        let x = self.x + (self.width - width) / 2;
+        let y = self.y + (self.height - height) / 2;
+
+        Rect::new(x, y, width, height).align(align)
+    }
+}
+
+/// How the source is mapped onto the destination rectangle.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub enum AspectMode {
+    /// Cover the whole rectangle, ignoring the aspect ratio.
+    Stretch,
+    /// Keep the aspect ratio and fit inside the rectangle.
+    /// The rest of the rectangle is left untouched.
+    Fit,
+    /// Like `Fit`, but fill the rest of the rectangle with the pad color.
+    Letterbox,
+    /// Keep the aspect ratio and cover the whole rectangle,
+    /// cropping the source evenly on both sides.
+    Fill,
+}
+
+/// Selects which part of the source is scaled to which part of the destination.
+///
+/// Positions are rounded down to multiples of the chroma subsampling factors.
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
+pub struct ScaleRegion {
+    /// The part of the source to scale. Defaults to the whole source.
+    pub src_crop: Option<Rect>,
+    /// The part of the destination to scale into. Defaults to the whole destination.
+    pub dst_rect: Option<Rect>,
+    pub aspect: AspectMode,
+    /// The RGB color used by `AspectMode::Letterbox`.
+    pub pad_color: (u8, u8, u8),
+}
+
+impl Default for ScaleRegion {
+    fn default() -> Self {
+        ScaleRegion {
+            src_crop: None,
+            dst_rect: None,
+            aspect: AspectMode::Stretch,
+            pad_color: (0, 0, 0),
+        }
+    }
+}
+
+struct Layout {
+    src: Rect,
+    dst_outer: Rect,
+    dst: Rect,
+}
+
+impl ScaleRegion {
+    fn layout(&self,
+        src_size: (usize, usize), src_align: (usize, usize), src_sar: f64,
+        dst_size: (usize, usize), dst_align: (usize, usize), dst_sar: f64,
+    ) -> Result<Layout> {
+        let src = self.src_crop.unwrap_or(Rect::new(0, 0, src_size.0, src_size.1));
+        let dst = self.dst_rect.unwrap_or(Rect::new(0, 0, dst_size.0, dst_size.1));
+
+        if !src.is_inside(src_size) {
+            bail!("Scale source crop {:?} invalid for dimension {} x {}", src, src_size.0, src_size.1);
+        }
+
+        if !dst.is_inside(dst_size) {
+            bail!("Scale target rect {:?} invalid for dimension {} x {}", dst, dst_size.0, dst_size.1);
+        }
+
+        let src = src.align(src_align);
+        let dst_outer = dst.align(dst_align);
+
+        // The display aspect ratios
+        let src_aspect = src.width as f64 * src_sar / src.height as f64;
+        let dst_aspect = dst_outer.width as f64 * dst_sar / dst_outer.height as f64;
+
+        let layout = match self.aspect {
+            AspectMode::Stretch => Layout {
+                src: src,
+                dst_outer: dst_outer,
+                dst: dst_outer,
+            },
+            AspectMode::Fit | AspectMode::Letterbox => {
+                let dst = if src_aspect > dst_aspect {
+                    let height = dst_outer.width as f64 * dst_sar / src_aspect;
+                    dst_outer.centered(dst_outer.width as f64, height, dst_align)
+                } else {
+                    let width = dst_outer.height as f64 * src_aspect / dst_sar;
+                    dst_outer.centered(width, dst_outer.height as f64, dst_align)
+                };
+
+                Layout {
+                    src: src,
+                    dst_outer: dst_outer,
+                    dst: dst,
+                }
+            },
+            AspectMode::Fill => {
+                let src = if src_aspect > dst_aspect {
+                    let width = src.height as f64 * dst_aspect / src_sar;
+                    src.centered(width, src.height as f64, src_align)
+                } else {
+                    let height = src.width as f64 * src_sar / dst_aspect;
+                    src.centered(src.width as f64, height, src_align)
+                };
+
+                Layout {
+                    src: src,
+                    dst_outer: dst_outer,
+                    dst: dst_outer,
+                }
+            },
+        };
+
+        Ok(layout)
+    }
+}
+
 struct SwsContext(*mut ffi::SwsContext);
 
 impl SwsContext {
     fn new(
-        source_width: i32, source_height: i32, source_pixel_format: AVPixelFormat,
-        target_width: i32, target_height: i32, target_pixel_format: AVPixelFormat,
+        source_width: i32, source_height: i32, source_pixel_format: PixelFormat,
+        target_width: i32, target_height: i32, target_pixel_format: PixelFormat,
         flags: i32,
     ) -> Result<Self> {
         unsafe {
@@ -195,8 +632,8 @@ impl SwsContext {
             }
 
             let scaler = ffi::sws_getContext(
-                source_width, source_height, source_pixel_format,
-                target_width, target_height, target_pixel_format,
+                source_width, source_height, source_pixel_format.as_raw(),
+                target_width, target_height, target_pixel_format.as_raw(),
                 flags,
                 source_filter,
                 target_filter,
@@ -211,6 +648,56 @@ impl SwsContext {
         }
     }
 
+    /// Apply the colorspaces and ranges of `options`,
+    /// keeping the defaults chosen by swscale for unset or unspecified values.
+    fn set_colorspace_details(&mut self, options: &ScalerOptions) -> Result<()> {
+        unsafe {
+            let mut inv_table = ptr::null_mut();
+            let mut src_range = 0;
+            let mut table = ptr::null_mut();
+            let mut dst_range = 0;
+            let mut brightness = 0;
+            let mut contrast = 0;
+            let mut saturation = 0;
+
+            let res = ffi::sws_getColorspaceDetails(self.0,
+                &mut inv_table, &mut src_range,
+                &mut table, &mut dst_range,
+                &mut brightness, &mut contrast, &mut saturation,
+            );
+            if res < 0 {
+                bail!("Could not get scaler colorspace details");
+            }
+
+            let mut inv_table = inv_table as *const c_int;
+            let mut table = table as *const c_int;
+
+            // The colorspace values match the `SWS_CS_*` constants
+            if let Some(colorspace) = options.src_colorspace.and_then(specified_colorspace) {
+                inv_table = ffi::sws_getCoefficients(colorspace.as_raw() as c_int);
+            }
+            if let Some(colorspace) = options.dst_colorspace.and_then(specified_colorspace) {
+                table = ffi::sws_getCoefficients(colorspace.as_raw() as c_int);
+            }
+            if let Some(range) = options.src_range.and_then(is_full_range) {
+                src_range = range as c_int;
+            }
+            if let Some(range) = options.dst_range.and_then(is_full_range) {
+                dst_range = range as c_int;
+            }
+
+            // This fails for YUV to YUV conversions, which don't use the tables.
+            // The ranges are still applied in that case.
+            ffi::sws_setColorspaceDetails(self.0,
+                inv_table, src_range,
+                table, dst_range,
+                brightness, contrast, saturation,
+            );
+
+            Ok(())
+        }
+    }
+
     unsafe fn free(&mut self) {
         if !self.0.is_null() {
             ffi::sws_freeContext(self.0);
@@ -223,6 +710,21 @@ impl SwsContext {
     }
 }
 
+fn specified_colorspace(colorspace: ColorSpace) -> Option<ColorSpace> {
+    match colorspace {
+        ColorSpace::Unspecified => None,
+        colorspace => Some(colorspace),
+    }
+}
+
+fn is_full_range(range: ColorRange) -> Option<bool> {
+    match range {
+        ColorRange::Jpeg => Some(true),
+        ColorRange::Mpeg => Some(false),
+        _ => None,
+    }
+}
+
 impl Drop for SwsContext {
     fn drop(&mut self) {
         unsafe {
@@ -233,8 +735,8 @@ impl Drop for SwsContext {
 
 #[cfg(test)]
 mod test {
-    use ffi::AVPixelFormat::*;
-    use super::Scaler;
+    use video::PixelFormat;
+    use super::{Scaler, ScalerOptions, ScalingAlgorithm, ScaleRegion, AspectMode, Rect};
 
     #[test]
     fn reusable() {
@@ -244,7 +746,7 @@ mod test {
         let source_width = 1;
         let source_linesize = &[3*source_width, 0, 0, 0];
         let source_height = 1;
-        let source_format = AV_PIX_FMT_RGB24;
+        let source_format = PixelFormat::Rgb24;
 
         let mut target: Vec<u8> = vec![
             1,2,3, 4,5,6, 7,8,9, 10,11,12,
@@ -260,7 +762,7 @@ mod test {
         let target_width = 4;
         let target_linesize = &[3*target_width, 0, 0, 0];
         let target_height = 4;
-        let target_format = AV_PIX_FMT_RGB24;
+        let target_format = PixelFormat::Rgb24;
 
         let mut scaler = Scaler::new();
 
@@ -283,14 +785,104 @@ mod test {
         ][..]);
     }
 
+    #[test]
+    fn options_change() {
+        let source = vec![0x10, 0x20, 0x30];
+        let source_data = &[&source[..], &[], &[], &[]];
+        let source_linesize = &[3, 0, 0, 0];
+
+        let mut target = vec![0; 2 * 2 * 3];
+        let target_data = &mut [&mut target[..], &mut [], &mut [], &mut []];
+        let target_linesize = &[2 * 3, 0, 0, 0];
+
+        let mut scaler = Scaler::new();
+
+        for &algorithm in &[ScalingAlgorithm::Bicubic, ScalingAlgorithm::Point, ScalingAlgorithm::Area] {
+            scaler.set_options(ScalerOptions {
+                algorithm: algorithm,
+                accurate_rounding: true,
+                .. ScalerOptions::default()
+            });
+
+            scaler.scale(
+                source_data, source_linesize, 1, 1, PixelFormat::Rgb24,
+                target_data, target_linesize, 2, 2, PixelFormat::Rgb24,
+            ).unwrap();
+
+            assert_eq!(scaler.context_options, scaler.options());
+            assert_eq!(target_data[0], &[0x10, 0x20, 0x30, 0x10, 0x20, 0x30, 0x10, 0x20, 0x30, 0x10, 0x20, 0x30][..]);
+        }
+    }
+
+    #[test]
+    fn letterbox_layout() {
+        let region = ScaleRegion {
+            aspect: AspectMode::Letterbox,
+            .. ScaleRegion::default()
+        };
+
+        let layout = region.layout(
+            (1920, 1080), (1, 1), 1.0,
+            (640, 640), (2, 2), 1.0,
+        ).unwrap();
+
+        assert_eq!(layout.src, Rect::new(0, 0, 1920, 1080));
+        assert_eq!(layout.dst_outer, Rect::new(0, 0, 640, 640));
+        assert_eq!(layout.dst, Rect::new(0, 140, 640, 360));
+    }
+
+    #[test]
+    fn fill_layout() {
+        let region = ScaleRegion {
+            src_crop: Some(Rect::new(1, 1, 1440, 1078)),
+            aspect: AspectMode::Fill,
+            .. ScaleRegion::default()
+        };
+
+        let layout = region.layout(
+            (1442, 1080), (2, 2), 1.0,
+            (1280, 720), (2, 2), 1.0,
+        ).unwrap();
+
+        assert_eq!(layout.src, Rect::new(0, 134, 1440, 810));
+        assert_eq!(layout.dst, Rect::new(0, 0, 1280, 720));
+    }
+
+    #[test]
+    fn region_outside_source() {
+        let region = ScaleRegion {
+            src_crop: Some(Rect::new(10, 0, 100, 100)),
+            .. ScaleRegion::default()
+        };
+
+        assert!(region.layout((100, 100), (1, 1), 1.0, (10, 10), (1, 1), 1.0).is_err());
+    }
+
+    #[test]
+    fn region_overflow() {
+        let region = ScaleRegion {
+            src_crop: Some(Rect::new(::std::usize::MAX, 0, 1, 1)),
+            .. ScaleRegion::default()
+        };
+
+        assert!(region.layout((100, 100), (1, 1), 1.0, (10, 10), (1, 1), 1.0).is_err());
+
+        let region = ScaleRegion {
+            dst_rect: Some(Rect::new(0, ::std::usize::MAX, 1, 1)),
+            .. ScaleRegion::default()
+        };
+
+        assert!(region.layout((100, 100), (1, 1), 1.0, (10, 10), (1, 1), 1.0).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn not_enough_planes() {
         let mut scaler = Scaler::new();
 
         scaler.scale(
-            &    [], &    [], 1, 1, AV_PIX_FMT_RGB24,
-            &mut [], &mut [], 1, 1, AV_PIX_FMT_RGB24,
+            &    [], &    [], 1, 1, PixelFormat::Rgb24,
+            &mut [], &mut [], 1, 1, PixelFormat::Rgb24,
         ).unwrap();
     }
 
@@ -300,8 +892,8 @@ mod test {
         let mut scaler = Scaler::new();
 
         scaler.scale(
-            &    [], &    [], 0, 0, AV_PIX_FMT_RGB24,
-            &mut [], &mut [], 0, 0, AV_PIX_FMT_RGB24,
+            &    [], &    [], 0, 0, PixelFormat::Rgb24,
+            &mut [], &mut [], 0, 0, PixelFormat::Rgb24,
         ).unwrap();
     }
 }